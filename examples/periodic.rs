@@ -0,0 +1,33 @@
+extern crate linux_embedded_hal as hal;
+extern crate sht3x;
+
+use std::thread;
+use std::time::Duration;
+
+use hal::{Delay, I2cdev};
+use sht3x::{Address, Rate, Repeatability, Sht3x, Status};
+
+fn main() {
+    let dev = I2cdev::new("/dev/i2c-1").unwrap();
+    let mut sht31 = Sht3x::new(dev, Address::Low);
+
+    sht31.start_periodic(Rate::R1, Repeatability::High, &mut Delay).unwrap();
+    loop {
+        thread::sleep(Duration::from_secs(1));
+
+        if sht31.check_and_recover(&mut Delay).unwrap() {
+            println!("Sensor was reset, periodic mode restarted");
+            continue;
+        }
+        let status = sht31.status(&mut Delay).unwrap();
+        if status.contains(Status::ALERT_PENDING) {
+            println!("Alert pending: {:?}", status);
+        }
+
+        match sht31.fetch_data(&mut Delay) {
+            Ok(m) => println!("{}", m),
+            Err(sht3x::Error::I2c(_)) => println!("No new data yet"),
+            Err(e) => println!("Error: {:?}", e),
+        }
+    }
+}
@@ -2,7 +2,7 @@ extern crate linux_embedded_hal as hal;
 extern crate sht3x;
 
 use hal::{Delay, I2cdev};
-use sht3x::{Sht3x, Address, Repeatability};
+use sht3x::{Sht3x, Address, ClockStretch, Repeatability};
 
 fn main() {
     println!("Hello, SHT31!");
@@ -10,9 +10,9 @@ fn main() {
     let dev = I2cdev::new("/dev/i2c-1").unwrap();
     let mut sht31 = Sht3x::new(dev, Address::Low);
 
-    println!("Status raw: {:?}", sht31.status().unwrap());
+    println!("Status: {:?}", sht31.status(&mut Delay).unwrap());
     loop {
-        let m = sht31.measure(Repeatability::High, &mut Delay).unwrap();
-        println!("Temp: {:.2} Humidity: {:.2}", m.temperature, m.humidity);
+        let m = sht31.measure(ClockStretch::Disabled, Repeatability::High, &mut Delay).unwrap();
+        println!("Temp: {:.2} Humidity: {:.2}", m.temperature as f32 / 100.0, m.humidity as f32 / 100.0);
     }
 }
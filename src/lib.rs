@@ -1,10 +1,68 @@
 //! Driver for Sensirion SHT3x-DIS digital temperature/humidity sensors
+//!
+//! # Features
+//!
+//! - `status-parse` (default): the `Status` type, `Sht3x::status` and
+//!   `Sht3x::diagnostics`. `Sht3x::status_raw` is always available.
+//! - `float` (default): `f32` accessors on `Measurement` (`temperature_celsius`,
+//!   `humidity_percent`, `dew_point_celsius`, `dew_point_spread_celsius`,
+//!   `snapshot`, ...). Without it the crate contains no floating-point code.
+//! - `async`: `Sht3x::measure_async`, `Sht3x::measure_retry` and
+//!   `Sht3x::measurement_stream` for `embedded-hal-async` buses and delays.
+//! - `defmt`: `defmt::Format` for `Measurement`, `EnvironmentSnapshot`,
+//!   `DriverState` and the types they contain.
+//! - `defmt-stream`: `Sht3x::measure_and_log`, which logs every reading with
+//!   `defmt`. Enables `defmt`.
+//! - `bus-recovery`: `check_bus` and `recover_bus` for buses stuck with SDA
+//!   low. Enables `embedded-hal`'s `unproven` feature for `InputPin`.
+//! - `fixed`: fixed-point accessors using the `fixed` crate.
+//! - `serde`: `Serialize` and `Deserialize` for `Config` and the types it
+//!   contains, and for `EnvironmentSnapshot`.
+//! - `log`: trace commands and warn about CRC mismatches using the `log` crate.
+//! - `lut-convert`: `LutConversion`, a division-free conversion.
+//! - `display-string`: `Measurement::to_display_string` for small displays,
+//!   using `heapless`.
+//! - `dry-run`: `Sht3x::with_dry_run`, which records commands instead of
+//!   sending them, and `NoBus`, for testing application code without a bus.
+//!
+//! # `embedded-hal` versions
+//!
+//! The blocking API uses the `embedded-hal` 0.2 traits, as does every
+//! release of this crate so far; only the `async` feature uses the 1.0
+//! `embedded-hal-async` traits.
 
 #![no_std]
 
+use core::fmt;
+use core::marker::PhantomData;
+
+#[cfg(feature = "status-parse")]
 use bitflags::bitflags;
 use embedded_hal::blocking::delay::DelayMs;
+#[cfg(feature = "bus-recovery")]
+use embedded_hal::{blocking::delay::DelayUs, digital::v2::{InputPin, OutputPin}};
 use embedded_hal::blocking::i2c::{Read, Write, WriteRead};
+#[cfg(feature = "async")]
+use embedded_hal_async::delay::DelayNs;
+
+// Logging that compiles out entirely without the `log` feature.
+macro_rules! trace {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "log")]
+        {
+            log::trace!($($arg)*);
+        }
+    };
+}
+
+macro_rules! warn {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "log")]
+        {
+            log::warn!($($arg)*);
+        }
+    };
+}
 
 // 2.2 Timing Specification for the Sensor System
 // Table 4
@@ -14,277 +72,6587 @@ const SOFT_RESET_TIME_MS: u8 = 1;
 // 4: Operation and Communication
 const COMMAND_WAIT_TIME_MS: u8 = 1;
 
+// 1.1 Relative Humidity, 1.2 Temperature
+// Table 1, Table 2
+const TEMP_MIN_CENTI: i32 = -4000;
+const TEMP_MAX_CENTI: i32 = 12500;
+const RH_MIN_CENTI: u16 = 0;
+const RH_MAX_CENTI: u16 = 10000;
+
 #[derive(Debug, Clone)]
-pub struct Sht3x<I2C> {
+pub struct Sht3x<I2C, C = SensirionConversion> {
     i2c: I2C,
-    address: Address,
+    address: u8,
+    mode: Mode,
+    crc: Crc,
+    auto_break: bool,
+    heater: bool,
+    verify_crc: bool,
+    read_count: u32,
+    clock_stretch: bool,
+    cs: ClockStretch,
+    rpt: Repeatability,
+    calibration: Calibration,
+    alert_limits: Option<AlertLimits>,
+    min_interval_ms: u32,
+    last_reading: Option<(u32, Measurement)>,
+    last_reported: Option<Measurement>,
+    verify_commands: bool,
+    on_measurement: Option<fn(&Measurement)>,
+    conversion_delay_ms: Option<u8>,
+    crc_error_count: u32,
+    i2c_error_count: u32,
+    auto_recover_every: u16,
+    fetches_since_check: u16,
+    last_status: Option<u16>,
+    #[cfg(feature = "dry-run")]
+    dry_run: Option<DryRun>,
+    conversion: PhantomData<C>,
 }
 
-impl<I2C, E> Sht3x<I2C>
-where
-    I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
-{
-    /// Creates a new driver.
-    pub const fn new(i2c: I2C, address: Address) -> Self {
-        Self { i2c, address }
+impl<I2C, C> Sht3x<I2C, C> {
+    /// Minimum operating temperature in centidegrees Celsius
+    pub const TEMP_MIN_CENTI: i32 = TEMP_MIN_CENTI;
+    /// Maximum operating temperature in centidegrees Celsius
+    pub const TEMP_MAX_CENTI: i32 = TEMP_MAX_CENTI;
+    /// Minimum relative humidity in centi-percent
+    pub const RH_MIN_CENTI: u16 = RH_MIN_CENTI;
+    /// Maximum relative humidity in centi-percent
+    pub const RH_MAX_CENTI: u16 = RH_MAX_CENTI;
+
+    // 4.13 Conversion of Signal Output: the 16-bit output spans 175 °C and
+    // 100 %RH, so one count is 175 / 65535 °C and 100 / 65535 %RH.
+    /// Temperature step of one raw count in micro-degrees Celsius, about
+    /// 0.0027 °C. It doesn't depend on the [`Repeatability`]; the noise
+    /// does, see [`Repeatability::noise_temp_centi`], which is what limits
+    /// the digits worth displaying.
+    pub const RAW_STEP_TEMP_MICRO: u32 = 2670;
+    /// Humidity step of one raw count in micro-percent, about 0.0015 %RH,
+    /// see [`RAW_STEP_TEMP_MICRO`](Self::RAW_STEP_TEMP_MICRO) and
+    /// [`Repeatability::noise_rh_centi`].
+    pub const RAW_STEP_RH_MICRO: u32 = 1526;
+
+    // The interface supports I2C fast mode plus, and a clock stretched
+    // measurement holds SCL for up to the measurement duration (Table 4).
+    /// Maximum I2C clock frequency (fast mode plus) in hertz
+    pub const MAX_I2C_FREQ_HZ: u32 = 1_000_000;
+    /// Longest time the sensor can hold SCL low while clock stretching, in
+    /// microseconds. This is the high repeatability measurement duration.
+    pub const MAX_CLOCK_STRETCH_US: u32 = Repeatability::High.max_duration() as u32 * 1000;
+
+    /// Whether a HAL clock stretch timeout of `timeout_us` microseconds is
+    /// long enough for clock stretched measurements at any repeatability.
+    pub const fn supports_clock_stretch_timeout(timeout_us: u32) -> bool {
+        timeout_us >= Self::MAX_CLOCK_STRETCH_US
     }
 
-    /// Send an I2C command.
-    fn command<D: DelayMs<u8>>(&mut self, command: Command, delay: &mut D, wait_time: Option<u8>) -> Result<(), Error<E>> {
-        let cmd_bytes = command.value().to_be_bytes();
-        self.i2c
-            .write(self.address as u8, &cmd_bytes)
-            .map_err(Error::I2c)?;
+    /// Use a custom conversion from raw readings, see [`Conversion`].
+    pub fn with_conversion<C2: Conversion>(self) -> Sht3x<I2C, C2> {
+        Sht3x {
+            i2c: self.i2c,
+            address: self.address,
+            mode: self.mode,
+            crc: self.crc,
+            auto_break: self.auto_break,
+            heater: self.heater,
+            verify_crc: self.verify_crc,
+            read_count: self.read_count,
+            clock_stretch: self.clock_stretch,
+            cs: self.cs,
+            rpt: self.rpt,
+            calibration: self.calibration,
+            alert_limits: self.alert_limits,
+            min_interval_ms: self.min_interval_ms,
+            last_reading: self.last_reading,
+            last_reported: self.last_reported,
+            verify_commands: self.verify_commands,
+            on_measurement: self.on_measurement,
+            conversion_delay_ms: self.conversion_delay_ms,
+            crc_error_count: self.crc_error_count,
+            i2c_error_count: self.i2c_error_count,
+            auto_recover_every: self.auto_recover_every,
+            fetches_since_check: self.fetches_since_check,
+            last_status: self.last_status,
+            #[cfg(feature = "dry-run")]
+            dry_run: self.dry_run,
+            conversion: PhantomData,
+        }
+    }
 
-        delay.delay_ms(wait_time.unwrap_or(0).max(COMMAND_WAIT_TIME_MS));
+    /// Use non-standard CRC parameters.
+    ///
+    /// The default, [`Crc::SENSIRION`], matches genuine Sensirion parts. This
+    /// is only needed for compatible clones that use a different CRC.
+    pub fn with_crc(mut self, crc: Crc) -> Self {
+        self.crc = crc;
+        self
+    }
 
-        Ok(())
+    /// Issue a break command before every single shot measurement.
+    ///
+    /// This makes sure the sensor is out of periodic mode even if it was left
+    /// there by a previous run of the firmware, at the cost of an extra
+    /// command write and its 1 ms wait per measurement. Disabled by default.
+    pub fn with_auto_break(mut self, enabled: bool) -> Self {
+        self.auto_break = enabled;
+        self
     }
 
-    /// Take a temperature and humidity measurement.
-    pub fn measure<D: DelayMs<u8>>(&mut self, cs: ClockStretch, rpt: Repeatability, delay: &mut D) -> Result<Measurement, Error<E>> {
-        self.command(Command::SingleShot(cs, rpt), delay, Some(rpt.max_duration()))?;
-        let mut buf = [0; 6];
-        self.i2c.read(self.address as u8, &mut buf)
-                .map_err(Error::I2c)?;
+    /// Enable or disable CRC validation of measurements.
+    ///
+    /// Enabled by default. Disabling it saves two CRC computations per
+    /// measurement, but corrupted readings are then returned as if they were
+    /// valid. Only consider this on short, clean buses where the cycles
+    /// matter, such as high rate periodic acquisition on a slow MCU.
+    pub fn with_verify_crc(mut self, enabled: bool) -> Self {
+        self.verify_crc = enabled;
+        self
+    }
 
-        let temperature = check_crc([buf[0], buf[1]], buf[2])
-            .map(convert_temperature)?;
-        let humidity = check_crc([buf[3], buf[4]], buf[5])
-            .map(convert_humidity)?;
+    /// Set the clock stretching and repeatability used by
+    /// [`measure_configured`](Self::measure_configured). Defaults to no clock
+    /// stretching and high repeatability.
+    pub fn with_settings(mut self, cs: ClockStretch, rpt: Repeatability) -> Self {
+        self.cs = cs;
+        self.rpt = rpt;
+        self
+    }
 
-        Ok(Measurement{ temperature, humidity })
+    /// Set the calibration applied by
+    /// [`measure_configured`](Self::measure_configured).
+    pub fn with_calibration(mut self, calibration: Calibration) -> Self {
+        self.calibration = calibration;
+        self
     }
 
-    /// Soft reset the sensor.
-    pub fn reset<D: DelayMs<u8>>(&mut self, delay: &mut D) -> Result<(), Error<E>> {
-        self.command(Command::SoftReset, delay, Some(SOFT_RESET_TIME_MS))
+    /// Check the status register after every configuration command.
+    ///
+    /// Heater, periodic mode, ART, break and alert limit commands are then
+    /// followed by a status read, and fail with [`Error::CommandRejected`] if
+    /// the sensor reports that the command wasn't executed, see
+    /// [`last_command_ok`](Self::last_command_ok). This costs an extra write
+    /// and 3-byte read per command. Disabled by default.
+    pub fn with_verify_commands(mut self, enabled: bool) -> Self {
+        self.verify_commands = enabled;
+        self
     }
 
-    /// Read the status register.
-    pub fn status<D: DelayMs<u8>>(&mut self, delay: &mut D) -> Result<Status, Error<E>> {
-        self.command(Command::Status, delay, None)?;
-        let mut buf = [0; 3];
-        self.i2c
-            .read(self.address as u8, &mut buf)
-            .map_err(Error::I2c)?;
+    /// Call `hook` with every measurement the driver reads, e.g. to feed
+    /// metrics or a display.
+    ///
+    /// This covers every single shot, periodic and ART method, blocking or
+    /// async. The hook runs synchronously on the read path, before the
+    /// measurement is returned, so it should be fast. It isn't called for
+    /// failed reads, for readings with a CRC mismatch returned by
+    /// [`measure_lenient`](Sht3x::measure_lenient), or for
+    /// [`measure_raw`](Sht3x::measure_raw), which doesn't convert.
+    pub fn with_on_measurement(mut self, hook: fn(&Measurement)) -> Self {
+        self.on_measurement = Some(hook);
+        self
+    }
 
-        let status = check_crc([buf[0], buf[1]], buf[2])?;
-        Ok(Status::from_bits_truncate(status))
+    /// Wait `ms` milliseconds for single shot conversions instead of the
+    /// datasheet maximum for the repeatability (4 / 6 / 15 ms).
+    ///
+    /// Typical conversions finish sooner, so parts characterized by the user
+    /// can get by with less. Reading too early without clock stretching
+    /// makes the sensor NACK the read, reported as [`Error::I2c`]; with clock
+    /// stretching the sensor holds the bus instead. The same wait is used
+    /// for every repeatability.
+    pub fn with_conversion_delay_ms(mut self, ms: u8) -> Self {
+        self.conversion_delay_ms = Some(ms);
+        self
     }
 
-    /// Clear the status register.
-    pub fn clear_status<D: DelayMs<u8>>(&mut self, delay: &mut D) -> Result<(), Error<E>> {
-        self.command(Command::ClearStatus, delay, None)
+    /// The time to wait for a single shot conversion at `rpt`.
+    fn conversion_delay(&self, rpt: Repeatability) -> u8 {
+        self.conversion_delay_ms.unwrap_or(rpt.max_duration())
     }
-}
 
-const fn convert_temperature(raw: u16) -> i32 {
-    -4500 + (17500 * raw as i32) / 65535
-}
+    /// Check for an unexpected sensor reset on every `every`-th
+    /// [`fetch_data`](Self::fetch_data), recovering like
+    /// [`check_and_recover`](Self::check_and_recover). 0 disables it, the
+    /// default.
+    ///
+    /// Each check costs one status read, a 2-byte write and a 3-byte read,
+    /// before the fetch; after a reset it also clears the status and restarts
+    /// periodic acquisition, so that fetch usually fails with a NACK as the
+    /// restarted sensor has no data yet. Recoveries are logged with the `log`
+    /// feature. The sensor also flags a reset on power-up, so clear the
+    /// status during initialization to avoid a needless restart.
+    pub fn with_auto_recover_periodic(mut self, every: u16) -> Self {
+        self.auto_recover_every = every;
+        self
+    }
 
-const fn convert_humidity(raw: u16) -> u16 {
-    ((10000 * raw as u32) / 65535) as u16
-}
+    /// Enable dry-run mode, for testing application code without a bus.
+    ///
+    /// The driver then never touches the bus. Commands are recorded, see
+    /// [`dry_run_log`](Self::dry_run_log), and succeed; measurements and
+    /// fetches return `measurement`, and every other read (status, alert
+    /// limits, serial number) returns zero words. Everything else works as
+    /// usual, e.g. the tracked mode and heater state follow the commands.
+    /// Pair it with [`NoBus`] if there is no bus to pass.
+    #[cfg(feature = "dry-run")]
+    pub fn with_dry_run(mut self, measurement: Measurement) -> Self {
+        self.dry_run = Some(DryRun { measurement, log: [0; DRY_RUN_LOG_LEN], len: 0, measuring: false });
+        self
+    }
 
-/// Compare the CRC of the input array to the given CRC checksum.
-fn check_crc<E>(data: [u8; 2], crc: u8) -> Result<u16, Error<E>> {
-    let calculated_crc = crc8(data);
+    /// The command words sent in dry-run mode, oldest first.
+    ///
+    /// Holds the first 32 commands; later ones aren't recorded until
+    /// [`clear_dry_run_log`](Self::clear_dry_run_log) is called. Empty if
+    /// not in dry-run mode.
+    #[cfg(feature = "dry-run")]
+    pub fn dry_run_log(&self) -> &[u16] {
+        match &self.dry_run {
+            Some(dry_run) => &dry_run.log[..dry_run.len],
+            None => &[],
+        }
+    }
 
-    if calculated_crc == crc {
-        Ok(u16::from_be_bytes(data))
-    } else {
-        Err(Error::Crc)
+    /// Empty the [`dry_run_log`](Self::dry_run_log).
+    #[cfg(feature = "dry-run")]
+    pub fn clear_dry_run_log(&mut self) {
+        if let Some(dry_run) = &mut self.dry_run {
+            dry_run.len = 0;
+        }
     }
-}
 
-/// Calculate the CRC8 checksum for the given input array.
-fn crc8(data: [u8; 2]) -> u8 {
-    let mut crc: u8 = 0xff;
+    /// Set the minimum time between two real measurements taken with
+    /// [`measure_rate_limited`](Self::measure_rate_limited). Defaults to 0.
+    pub fn with_min_interval(mut self, min_interval_ms: u32) -> Self {
+        self.min_interval_ms = min_interval_ms;
+        self
+    }
 
-    for byte in data {
-        crc ^= byte;
+    /// The driver's settings, e.g. to persist them, see [`Config`].
+    ///
+    /// The alert limits are the ones last written with
+    /// [`set_alert_limits`](Self::set_alert_limits), or `None` if they
+    /// haven't been written since the driver was created or the sensor reset.
+    pub const fn to_config(&self) -> Config {
+        Config {
+            address: self.address,
+            clock_stretch: self.cs,
+            repeatability: self.rpt,
+            calibration: self.calibration,
+            alert_limits: self.alert_limits,
+        }
+    }
 
-        for _ in 0..8 {
-            if crc & 0x80 > 0 {
-                crc = (crc << 1) ^ 0x31;
-            } else {
-                crc <<= 1;
-            }
+    /// Number of successful measurements (single shot or fetched).
+    ///
+    /// Comparing the count over time shows whether a read loop is still
+    /// making progress. It wraps around to 0 after `u32::MAX`.
+    pub const fn read_count(&self) -> u32 {
+        self.read_count
+    }
+
+    /// Whether the most recent single shot measurement requested clock
+    /// stretching.
+    ///
+    /// This is the command the driver sent, not a confirmation that the HAL
+    /// actually let the sensor stretch the clock. `false` before the first
+    /// measurement.
+    pub const fn last_used_clock_stretch(&self) -> bool {
+        self.clock_stretch
+    }
+
+    /// Everything the driver tracks about the sensor, for debug output and
+    /// bug reports. Doesn't touch the bus.
+    ///
+    /// This is the driver's view, which can differ from the sensor's after
+    /// an unexpected reset or if another bus master changed its settings,
+    /// see [`check_and_recover`](Self::check_and_recover).
+    pub const fn debug_state(&self) -> DriverState {
+        DriverState {
+            address: self.address,
+            mode: self.mode,
+            heater: self.heater,
+            last_used_clock_stretch: self.clock_stretch,
+            read_count: self.read_count,
+            alert_limits: self.alert_limits,
         }
     }
 
-    crc
-}
+    /// The acquisition mode the driver last configured.
+    pub const fn mode(&self) -> Mode {
+        self.mode
+    }
 
-/// Errors
-#[derive(Debug)]
-pub enum Error<E> {
-    /// Wrong CRC
-    Crc,
-    /// I2C bus error
-    I2c(E),
-}
+    /// Humidity response time τ63% in the tracked acquisition mode, in
+    /// milliseconds.
+    ///
+    /// 1.1 Relative Humidity, Table 1: 8 s at 25 °C and 1 m/s airflow, for
+    /// single shot and periodic acquisition. The datasheet doesn't give a
+    /// value for ART mode, which responds faster, so this is `None` then.
+    /// Temperature responds in more than 2 s depending on how the sensor is
+    /// mounted (Table 2).
+    pub const fn tau_63_ms(&self) -> Option<u16> {
+        match self.mode {
+            Mode::SingleShot | Mode::Periodic(..) => Some(8000),
+            Mode::Art => None,
+        }
+    }
 
-/// I2C address
-#[derive(Debug, Copy, Clone)]
-pub enum Address {
-    /// Address pin held high
-    High = 0x45,
-    /// Address pin held low
-    Low = 0x44,
-}
+    /// Whether a new periodic measurement should be available, `elapsed_ms`
+    /// after the last one was fetched (or acquisition was started).
+    ///
+    /// The sensor has no data-ready flag, so this is only an estimate based
+    /// on the configured [`Rate`]: the sensor's clock isn't exact, and
+    /// [`fetch_data`](Self::fetch_data) can still find no new data. Always
+    /// `false` outside periodic mode.
+    pub const fn estimate_ready(&self, elapsed_ms: u16) -> bool {
+        match self.mode {
+            Mode::Periodic(rate, _) => elapsed_ms >= rate.interval_ms(),
+            Mode::Art => elapsed_ms >= Rate::R4.interval_ms(),
+            Mode::SingleShot => false,
+        }
+    }
 
-/// Clock stretching
-#[derive(Debug)]
-pub enum ClockStretch {
-    Enabled,
-    Disabled,
-}
+    /// Whether `m` differs enough from the last reported measurement to be
+    /// worth reporting, for report-on-change telemetry.
+    ///
+    /// Returns `true`, and makes `m` the new baseline, if either value moved
+    /// by more than its threshold (centidegrees and centi-percent), or if
+    /// nothing was reported yet. Otherwise the baseline is kept, so slow
+    /// drifts are still reported once they add up. [`Deadband`] does the
+    /// same outside the driver, except that it already reports a change of
+    /// exactly the threshold.
+    pub fn should_report(&mut self, m: &Measurement, temp_delta_centi: i32, rh_delta_centi: u16) -> bool {
+        let report = match self.last_reported {
+            Some(last) => {
+                let (dt, drh) = Measurement::max_disagreement(m, &last);
+                dt > temp_delta_centi || drh > rh_delta_centi
+            }
+            None => true,
+        };
+        if report {
+            self.last_reported = Some(*m);
+        }
+        report
+    }
 
-/// Periodic data acquisition rate
-#[allow(non_camel_case_types, unused)]
-enum Rate {
-    /// 0.5 measurements per second
-    R0_5,
-    /// 1 measurement per second
-    R1,
-    /// 2 measurements per second
-    R2,
-    /// 4 measurements per second
-    R4,
-    /// 10 measurements per second
-    R10,
-}
+    /// Convert a validated measurement frame and pass the result to the hook
+    /// set with [`with_on_measurement`](Self::with_on_measurement).
+    fn parse_hooked(&self, frame: &[u8; 6]) -> Measurement
+    where
+        C: Conversion,
+    {
+        let m = parse_frame_unchecked::<C>(frame);
+        if let Some(hook) = self.on_measurement {
+            hook(&m);
+        }
+        m
+    }
 
-#[derive(Copy, Clone)]
-pub enum Repeatability {
-    High,
-    Medium,
-    Low,
-}
+    /// Record `command` instead of sending it in dry-run mode. Returns
+    /// whether it was recorded.
+    #[cfg(feature = "dry-run")]
+    fn dry_run_command(&mut self, command: Command) -> bool {
+        match &mut self.dry_run {
+            Some(dry_run) => {
+                dry_run.record(command);
+                true
+            }
+            None => false,
+        }
+    }
 
-impl Repeatability {
-    /// Maximum measurement duration in milliseconds
-    const fn max_duration(&self) -> u8 {
-        match *self {
-            Repeatability::Low => 4,
-            Repeatability::Medium => 6,
-            Repeatability::High => 15,
+    #[cfg(not(feature = "dry-run"))]
+    fn dry_run_command(&mut self, _command: Command) -> bool {
+        false
+    }
+
+    /// Fill `buf` with the dry-run response instead of reading it. Returns
+    /// whether it was filled.
+    #[cfg(feature = "dry-run")]
+    fn dry_run_read(&self, buf: &mut [u8]) -> bool {
+        match &self.dry_run {
+            Some(dry_run) => {
+                dry_run.respond(buf, &self.crc);
+                true
+            }
+            None => false,
         }
     }
-}
 
-#[allow(unused)]
-enum Command {
-    SingleShot(ClockStretch, Repeatability),
-    Periodic(Rate, Repeatability),
-    FetchData,
-    PeriodicWithART,
-    Break,
-    SoftReset,
-    HeaterEnable,
-    HeaterDisable,
-    Status,
-    ClearStatus,
-}
+    #[cfg(not(feature = "dry-run"))]
+    fn dry_run_read(&self, _buf: &mut [u8]) -> bool {
+        false
+    }
 
-impl Command {
-    const fn value(&self) -> u16 {
-        use ClockStretch::Enabled as CSEnabled;
-        use ClockStretch::Disabled as CSDisabled;
-        use Rate::*;
-        use Repeatability::*;
-        match *self {
-            // 4.3 Measurement Commands for Single Shot Data Acquisition Mode
-            // Table 8
-            Command::SingleShot(CSEnabled,  High)   => 0x2C06,
-            Command::SingleShot(CSEnabled,  Medium) => 0x2C0D,
-            Command::SingleShot(CSEnabled,  Low)    => 0x2C10,
-            Command::SingleShot(CSDisabled, High)   => 0x2400,
-            Command::SingleShot(CSDisabled, Medium) => 0x240B,
-            Command::SingleShot(CSDisabled, Low)    => 0x2416,
+    /// The status word from the most recent successful status read, without
+    /// a bus transaction.
+    ///
+    /// Updated by every method that reads the status register, e.g. to see
+    /// which flag made [`measure_checked_status`](Sht3x::measure_checked_status)
+    /// fail. `None` until the status has been read at least once.
+    pub const fn last_status_raw(&self) -> Option<u16> {
+        self.last_status
+    }
 
-            // 4.5 Measurement Commands for Periodic Data Acquisition Mode
-            // Table 9
-            Command::Periodic(R0_5, High)   => 0x2032,
-            Command::Periodic(R0_5, Medium) => 0x2024,
-            Command::Periodic(R0_5, Low)    => 0x202F,
-            Command::Periodic(R1,   High)   => 0x2130,
-            Command::Periodic(R1,   Medium) => 0x2126,
-            Command::Periodic(R1,   Low)    => 0x212D,
-            Command::Periodic(R2,   High)   => 0x2236,
-            Command::Periodic(R2,   Medium) => 0x2220,
-            Command::Periodic(R2,   Low)    => 0x222B,
-            Command::Periodic(R4,   High)   => 0x2334,
-            Command::Periodic(R4,   Medium) => 0x2322,
-            Command::Periodic(R4,   Low)    => 0x2329,
-            Command::Periodic(R10,  High)   => 0x2737,
-            Command::Periodic(R10,  Medium) => 0x2721,
-            Command::Periodic(R10,  Low)    => 0x272A,
+    /// Number of CRC mismatches in data received from the sensor.
+    ///
+    /// Together with [`i2c_error_count`](Self::i2c_error_count) this shows
+    /// how healthy the bus is over a long deployment. It saturates at
+    /// `u32::MAX` instead of wrapping around; see
+    /// [`reset_counters`](Self::reset_counters).
+    pub const fn crc_error_count(&self) -> u32 {
+        self.crc_error_count
+    }
 
-            // 4.6 Readout of Measurement Results for Periodic Mode
-            // Table 10
-            Command::FetchData => 0xE000,
+    /// Number of failed I2C transactions, including NACKs, e.g. from
+    /// fetching periodic data before it's ready. Saturates like
+    /// [`crc_error_count`](Self::crc_error_count).
+    pub const fn i2c_error_count(&self) -> u32 {
+        self.i2c_error_count
+    }
 
-            // 4.7 ART command
-            // Table 11
-            Command::PeriodicWithART => 0x2B32,
+    /// Reset [`crc_error_count`](Self::crc_error_count) and
+    /// [`i2c_error_count`](Self::i2c_error_count) to 0.
+    pub fn reset_counters(&mut self) {
+        self.crc_error_count = 0;
+        self.i2c_error_count = 0;
+    }
 
-            // 4.8 Break command
-            // Table 12
-            Command::Break => 0x3093,
+    fn count_crc_error(&mut self) {
+        self.crc_error_count = self.crc_error_count.saturating_add(1);
+    }
 
-            // 4.9 Reset
-            // Table 13
-            Command::SoftReset => 0x30A2,
+    /// Count a failed bus transaction and wrap its error.
+    fn i2c_error<E>(&mut self, e: E) -> Error<E> {
+        self.i2c_error_count = self.i2c_error_count.saturating_add(1);
+        Error::I2c(e)
+    }
 
-            // 4.10 Heater
-            // Table 15
-            Command::HeaterEnable  => 0x306D,
-            Command::HeaterDisable => 0x3066,
+    /// Check the CRC of a received word, counting mismatches.
+    fn check_crc_counted(&mut self, data: [u8; 2], crc: u8) -> Result<u16, CrcError> {
+        check_crc(data, crc, &self.crc).inspect_err(|_| self.count_crc_error())
+    }
 
-            // 4.11 Status register
-            // Table 16
-            Command::Status => 0xF32D,
-            // Table 18
-            Command::ClearStatus => 0x3041,
+    /// Check a received measurement frame's CRCs, unless disabled, and count
+    /// it.
+    fn validate_frame<E>(&mut self, buf: &[u8; 6]) -> Result<(), Error<E>> {
+        if self.verify_crc {
+            check_words(buf, &self.crc).inspect_err(|_| self.count_crc_error())?;
         }
+
+        self.read_count = self.read_count.wrapping_add(1);
+        Ok(())
     }
 }
 
-#[derive(Debug)]
-pub struct Measurement {
-    pub temperature: i32,
-    pub humidity: u16,
+impl<I2C> Sht3x<I2C> {
+    /// Creates a new driver for an address found by [`detect`].
+    ///
+    /// The same as [`new`](Self::new), but the [`VerifiedAddress`] makes it
+    /// visible in the types that the sensor answered at boot. Plain `new`
+    /// remains for addresses known from the hardware design.
+    pub const fn new_probed(i2c: I2C, address: VerifiedAddress) -> Self {
+        Self::new(i2c, address.0)
+    }
+
+    /// Creates a new driver.
+    pub const fn new(i2c: I2C, address: Address) -> Self {
+        Self {
+            i2c,
+            address: address as u8,
+            mode: Mode::SingleShot,
+            crc: Crc::SENSIRION,
+            auto_break: false,
+            heater: false,
+            verify_crc: true,
+            read_count: 0,
+            clock_stretch: false,
+            cs: ClockStretch::Disabled,
+            rpt: Repeatability::High,
+            calibration: Calibration { temperature_offset: 0, humidity_offset: 0 },
+            alert_limits: None,
+            min_interval_ms: 0,
+            last_reading: None,
+            last_reported: None,
+            verify_commands: false,
+            on_measurement: None,
+            conversion_delay_ms: None,
+            crc_error_count: 0,
+            i2c_error_count: 0,
+            auto_recover_every: 0,
+            fetches_since_check: 0,
+            last_status: None,
+            #[cfg(feature = "dry-run")]
+            dry_run: None,
+            conversion: PhantomData,
+        }
+    }
 }
 
-bitflags! {
-    /// Status register
-    pub struct Status: u16 {
-        /// Alert pending status
-        const ALERT_PENDING         = 1 << 15;
-        /// Heater status
-        const HEATER                = 1 << 13;
-        /// RH tracking alert
-        const RH_TRACKING_ALERT     = 1 << 11;
-        /// T tracking alert
-        const T_TRACKING_ALERT      = 1 << 10;
-        /// System reset detected
-        const SYSTEM_RESET_DETECTED = 1 <<  4;
-        /// Command status
-        const COMMAND               = 1 <<  1;
-        /// Write data checksum status
-        const WRITE_DATA_CHECKSUM   = 1 <<  0;
+impl<I2C, E> Sht3x<I2C>
+where
+    I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+{
+    /// Creates a new driver for a sensor at an arbitrary 7-bit address.
+    ///
+    /// The sensor itself only responds to the [`Address`] values, but address
+    /// translators and some muxes can present it elsewhere on the bus.
+    /// Returns [`Error::InvalidAddress`] if `address` doesn't fit in 7 bits.
+    pub fn new_with_raw_address(i2c: I2C, address: u8) -> Result<Self, Error<E>> {
+        if address > 0x7F {
+            return Err(Error::InvalidAddress);
+        }
+        let mut sht = Self::new(i2c, Address::Low);
+        sht.address = address;
+        Ok(sht)
+    }
+
+    /// Creates a new driver from saved settings, see [`Config`].
+    ///
+    /// This doesn't touch the bus: the alert limits only exist on the sensor
+    /// and are lost on power-up, so restore them with
+    /// [`set_alert_limits`](Sht3x::set_alert_limits) afterwards. Returns
+    /// [`Error::InvalidAddress`] if the address doesn't fit in 7 bits.
+    pub fn from_config(i2c: I2C, config: &Config) -> Result<Self, Error<E>> {
+        Ok(Self::new_with_raw_address(i2c, config.address)?
+            .with_settings(config.clock_stretch, config.repeatability)
+            .with_calibration(config.calibration))
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+impl<I2C, E, C> Sht3x<I2C, C>
+where
+    I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+    C: Conversion,
+{
+    /// Send an I2C command.
+    fn command<D: DelayMs<u8>>(&mut self, command: Command, delay: &mut D, wait_time: Option<u8>) -> Result<(), Error<E>> {
+        trace!("command {:#06x} to {:#04x}", command.value(), self.address);
+        let cmd_bytes = command.value().to_be_bytes();
+        if !self.dry_run_command(command) {
+            self.i2c
+                .write(self.address, &cmd_bytes)
+                .map_err(|e| self.i2c_error(e))?;
+        }
 
-    #[test]
-    fn test_crc() {
-        assert_eq!(crc8([0xBE, 0xEF]), 0x92);
+        delay.delay_ms(wait_time.unwrap_or(0).max(COMMAND_WAIT_TIME_MS));
+
+        Ok(())
+    }
+
+    /// Send a configuration command, verifying it if enabled with
+    /// [`with_verify_commands`](Self::with_verify_commands).
+    fn write_command<D: DelayMs<u8>>(&mut self, command: Command, delay: &mut D) -> Result<(), Error<E>> {
+        self.command(command, delay, None)?;
+        if self.verify_commands && !self.last_command_ok(delay)? {
+            return Err(Error::CommandRejected);
+        }
+        Ok(())
+    }
+
+    /// Send an I2C command followed by a data word and its CRC.
+    fn command_with_data<D: DelayMs<u8>>(&mut self, command: Command, data: u16, delay: &mut D) -> Result<(), Error<E>> {
+        trace!("command {:#06x} with data {:#06x} to {:#04x}", command.value(), data, self.address);
+        let [c0, c1] = command.value().to_be_bytes();
+        let [d0, d1] = data.to_be_bytes();
+        if !self.dry_run_command(command) {
+            self.i2c
+                .write(self.address, &[c0, c1, d0, d1, self.crc.checksum([d0, d1])])
+                .map_err(|e| self.i2c_error(e))?;
+        }
+
+        delay.delay_ms(COMMAND_WAIT_TIME_MS);
+
+        Ok(())
+    }
+
+    /// Take a temperature and humidity measurement.
+    pub fn measure<D: DelayMs<u8>>(&mut self, cs: ClockStretch, rpt: Repeatability, delay: &mut D) -> Result<Measurement, Error<E>> {
+        self.single_shot(cs, rpt, delay)?;
+        self.read_measurement_hooked()
+    }
+
+    /// Take a measurement without converting it to physical units.
+    pub fn measure_raw<D: DelayMs<u8>>(&mut self, cs: ClockStretch, rpt: Repeatability, delay: &mut D) -> Result<RawMeasurement, Error<E>> {
+        self.single_shot(cs, rpt, delay)?;
+        let frame = self.read_frame()?;
+        Ok(RawMeasurement::from_frame(&frame))
+    }
+
+    /// Take a measurement and also return the frame it was parsed from.
+    ///
+    /// The frame is exactly what the sensor sent, including its original CRC
+    /// bytes, so gateways can forward it without re-encoding. The CRCs were
+    /// validated unless disabled with [`with_verify_crc`](Self::with_verify_crc).
+    pub fn measure_with_raw_frame<D: DelayMs<u8>>(&mut self, cs: ClockStretch, rpt: Repeatability, delay: &mut D) -> Result<(Measurement, [u8; 6]), Error<E>> {
+        self.single_shot(cs, rpt, delay)?;
+        let frame = self.read_frame()?;
+        Ok((self.parse_hooked(&frame), frame))
+    }
+
+    /// Take a measurement, reading the frame into the caller's `buf`.
+    ///
+    /// Lets callers keep the scratch storage in a static or a reused stack
+    /// slot instead of a fresh array per call. Whatever `buf` held before is
+    /// overwritten with the raw frame, even if the read then fails; the
+    /// returned measurement is a copy and doesn't borrow from it. Otherwise
+    /// behaves like [`measure`](Self::measure).
+    pub fn measure_with_buffer<D: DelayMs<u8>>(&mut self, cs: ClockStretch, rpt: Repeatability, delay: &mut D, buf: &mut [u8; 6]) -> Result<Measurement, Error<E>> {
+        self.single_shot(cs, rpt, delay)?;
+        self.read_words(buf)?;
+        self.validate_frame(buf)?;
+        Ok(self.parse_hooked(buf))
+    }
+
+    /// Take a measurement, returning it even if a CRC doesn't match, with a
+    /// flag that's `true` if both did.
+    ///
+    /// For loggers that keep suspect readings for later analysis. When the
+    /// flag is `false` the values may be garbage. Only bus errors and short
+    /// reads fail; CRC checks happen regardless of
+    /// [`with_verify_crc`](Self::with_verify_crc), and only readings with
+    /// valid CRCs count towards [`read_count`](Self::read_count).
+    pub fn measure_lenient<D: DelayMs<u8>>(&mut self, cs: ClockStretch, rpt: Repeatability, delay: &mut D) -> Result<(Measurement, bool), Error<E>> {
+        self.single_shot(cs, rpt, delay)?;
+        let mut buf = [0; 6];
+        self.read_words(&mut buf)?;
+        let crc_ok = check_words(&buf, &self.crc).is_ok();
+        if crc_ok {
+            self.read_count = self.read_count.wrapping_add(1);
+            Ok((self.parse_hooked(&buf), true))
+        } else {
+            self.count_crc_error();
+            Ok((parse_frame_unchecked::<C>(&buf), false))
+        }
+    }
+
+    /// Take the temperature at `temp_rpt` and the humidity at `rh_rpt`
+    /// repeatability.
+    ///
+    /// The sensor can't do this in one measurement, so this takes two and
+    /// combines the temperature of the first with the humidity of the
+    /// second, roughly doubling the latency. If both repeatabilities are the
+    /// same, it's a single [`measure`](Self::measure).
+    pub fn measure_split<D: DelayMs<u8>>(&mut self, cs: ClockStretch, temp_rpt: Repeatability, rh_rpt: Repeatability, delay: &mut D) -> Result<Measurement, Error<E>> {
+        let first = self.measure(cs, temp_rpt, delay)?;
+        if temp_rpt == rh_rpt {
+            return Ok(first);
+        }
+        let second = self.measure(cs, rh_rpt, delay)?;
+        Ok(Measurement { temperature: first.temperature, humidity: second.humidity })
+    }
+
+    /// Trigger a single shot measurement and wait for it to complete.
+    fn single_shot<D: DelayMs<u8>>(&mut self, cs: ClockStretch, rpt: Repeatability, delay: &mut D) -> Result<(), Error<E>> {
+        self.single_shot_with(cs, rpt, delay, || {})
+    }
+
+    /// Start a single shot measurement, calling `feed` right before and
+    /// after the conversion delay.
+    fn single_shot_with<D: DelayMs<u8>, F: FnMut()>(&mut self, cs: ClockStretch, rpt: Repeatability, delay: &mut D, mut feed: F) -> Result<(), Error<E>> {
+        if self.auto_break {
+            self.stop_periodic_force(delay)?;
+        }
+        feed();
+        let result = self.command(Command::SingleShot(cs, rpt), delay, Some(self.conversion_delay(rpt)));
+        feed();
+        result?;
+        self.clock_stretch = cs == ClockStretch::Enabled;
+        Ok(())
+    }
+
+    /// Take a temperature and humidity measurement, calling `feed` before
+    /// and after the conversion delay.
+    ///
+    /// Meant for petting a hardware watchdog whose budget might not cover
+    /// the up to 15 ms delay on top of whatever ran before. The delay itself
+    /// is still a single call to `delay`, so `feed` can't run during it.
+    pub fn measure_with_watchdog<D: DelayMs<u8>, F: FnMut()>(&mut self, cs: ClockStretch, rpt: Repeatability, delay: &mut D, feed: F) -> Result<Measurement, Error<E>> {
+        self.single_shot_with(cs, rpt, delay, feed)?;
+        self.read_measurement_hooked()
+    }
+
+    /// Take a measurement with the configured settings and calibration, see
+    /// [`with_settings`](Self::with_settings) and
+    /// [`with_calibration`](Self::with_calibration).
+    pub fn measure_configured<D: DelayMs<u8>>(&mut self, delay: &mut D) -> Result<Measurement, Error<E>> {
+        let m = self.measure(self.cs, self.rpt, delay)?;
+        Ok(self.calibration.apply(&m))
+    }
+
+    /// Take a measurement, unless the last one is less than the minimum
+    /// interval old, see [`with_min_interval`](Self::with_min_interval).
+    ///
+    /// `now_ms` comes from a monotonic millisecond clock supplied by the
+    /// caller, which may wrap around; it has to be the same clock on every
+    /// call. A cached reading is returned as [`Reading::Cached`] without
+    /// touching the bus.
+    pub fn measure_rate_limited<D: DelayMs<u8>>(&mut self, cs: ClockStretch, rpt: Repeatability, now_ms: u32, delay: &mut D) -> Result<Reading, Error<E>> {
+        if let Some((taken, m)) = self.last_reading {
+            if now_ms.wrapping_sub(taken) < self.min_interval_ms {
+                return Ok(Reading::Cached(m));
+            }
+        }
+        let m = self.measure(cs, rpt, delay)?;
+        self.last_reading = Some((now_ms, m));
+        Ok(Reading::Fresh(m))
+    }
+
+    /// Take a measurement now, ignoring the minimum interval, and make it the
+    /// cached reading for [`measure_rate_limited`](Self::measure_rate_limited).
+    ///
+    /// The CRCs are still checked. `now_ms` is the same clock as for
+    /// `measure_rate_limited`. Filters outside the driver are unaffected;
+    /// use [`Deadband::force`] to make a deadband report the fresh reading.
+    pub fn measure_forced<D: DelayMs<u8>>(&mut self, cs: ClockStretch, rpt: Repeatability, now_ms: u32, delay: &mut D) -> Result<Measurement, Error<E>> {
+        let m = self.measure(cs, rpt, delay)?;
+        self.last_reading = Some((now_ms, m));
+        Ok(m)
+    }
+
+    /// Take a measurement tagged with [`Source::SingleShot`].
+    pub fn measure_sourced<D: DelayMs<u8>>(&mut self, cs: ClockStretch, rpt: Repeatability, delay: &mut D) -> Result<SourcedMeasurement, Error<E>> {
+        let measurement = self.measure(cs, rpt, delay)?;
+        Ok(SourcedMeasurement { measurement, source: Source::SingleShot })
+    }
+
+    /// Take a measurement and log it with `defmt::info!`.
+    ///
+    /// A debugging aid for watching live readings over a probe; production
+    /// telemetry should handle the returned measurement itself.
+    #[cfg(feature = "defmt-stream")]
+    pub fn measure_and_log<D: DelayMs<u8>>(&mut self, cs: ClockStretch, rpt: Repeatability, delay: &mut D) -> Result<Measurement, Error<E>> {
+        let m = self.measure(cs, rpt, delay)?;
+        defmt::info!("sht3x {=u8:#x}: {=i32} c°C, {=u16} c%RH", self.address, m.temperature, m.humidity);
+        Ok(m)
+    }
+
+    /// Take a measurement and read the status register afterwards, for
+    /// continuous health monitoring.
+    ///
+    /// This costs one extra status transaction per measurement (a 2-byte
+    /// write and a 3-byte read); [`measure`](Self::measure) stays a single
+    /// measurement. The status flags aren't cleared. It's a method of its own
+    /// rather than a builder option for `measure`, since a runtime flag
+    /// couldn't change what `measure` returns.
+    pub fn measure_healthy<D: DelayMs<u8>>(&mut self, cs: ClockStretch, rpt: Repeatability, delay: &mut D) -> Result<HealthyMeasurement, Error<E>> {
+        let measurement = self.measure(cs, rpt, delay)?;
+        let status = self.status_raw(delay)?;
+        Ok(HealthyMeasurement {
+            measurement,
+            reset_detected: status & status_bits::SYSTEM_RESET_DETECTED != 0,
+            alert_pending: status & status_bits::ALERT_PENDING != 0,
+        })
+    }
+
+    /// Take a measurement without clock stretching, polling for the result
+    /// instead of waiting the full conversion time.
+    ///
+    /// The sensor NACKs reads until the conversion is done, so the read is
+    /// retried every millisecond. Before each retry `is_timed_out` is called;
+    /// it's the caller's time source, and once it returns `true` this fails
+    /// with [`Error::Timeout`]. Errors other than the NACK (reported by the
+    /// HAL as an I2C error) are returned right away.
+    pub fn measure_polled<D: DelayMs<u8>, F: FnMut() -> bool>(&mut self, rpt: Repeatability, delay: &mut D, mut is_timed_out: F) -> Result<Measurement, Error<E>> {
+        if self.auto_break {
+            self.stop_periodic_force(delay)?;
+        }
+        self.command(Command::SingleShot(ClockStretch::Disabled, rpt), delay, None)?;
+        self.clock_stretch = false;
+        loop {
+            match self.read_measurement_hooked() {
+                Err(Error::I2c(_)) => {
+                    if is_timed_out() {
+                        return Err(Error::Timeout);
+                    }
+                    delay.delay_ms(1);
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Take a measurement, failing with [`Error::UnexpectedReset`] if the
+    /// sensor reset since the status was last cleared.
+    ///
+    /// Reads the status register after the measurement, one extra 2-byte
+    /// write and 3-byte read. On a reset the reading is dropped, the status
+    /// is cleared, and the tracked heater and alert limit state is reset to
+    /// the sensor's defaults, so calling this again succeeds; restoring the
+    /// configuration is up to the caller. The sensor also sets the reset
+    /// flag on power-up, so clear the status once during initialization.
+    pub fn measure_checked_status<D: DelayMs<u8>>(&mut self, cs: ClockStretch, rpt: Repeatability, delay: &mut D) -> Result<Measurement, Error<E>> {
+        let m = self.measure(cs, rpt, delay)?;
+        if self.status_raw(delay)? & status_bits::SYSTEM_RESET_DETECTED != 0 {
+            self.heater = false;
+            self.alert_limits = None;
+            self.clear_status(delay)?;
+            return Err(Error::UnexpectedReset);
+        }
+        Ok(m)
+    }
+
+    /// Take a measurement and return the temperature and the humidity as a
+    /// [`Quantity`] each, see [`Measurement::quantities`].
+    pub fn measure_typed<D: DelayMs<u8>>(&mut self, cs: ClockStretch, rpt: Repeatability, delay: &mut D) -> Result<(Quantity, Quantity), Error<E>> {
+        Ok(self.measure(cs, rpt, delay)?.quantities())
+    }
+
+    /// Take the lowest-energy single shot measurement.
+    ///
+    /// Uses low repeatability without clock stretching: the conversion takes
+    /// at most 4 ms, at the cost of more noise than the other repeatabilities.
+    pub fn measure_low_power<D: DelayMs<u8>>(&mut self, delay: &mut D) -> Result<Measurement, Error<E>> {
+        self.measure(ClockStretch::Disabled, Repeatability::Low, delay)
+    }
+
+    /// Take a temperature and humidity measurement, storing it in `out`.
+    ///
+    /// `out` is left untouched if the measurement fails.
+    pub fn measure_into<D: DelayMs<u8>>(&mut self, cs: ClockStretch, rpt: Repeatability, delay: &mut D, out: &mut Measurement) -> Result<(), Error<E>> {
+        *out = self.measure(cs, rpt, delay)?;
+        Ok(())
+    }
+
+    /// Take a measurement and push it into `ring`, evicting the oldest
+    /// reading if it's full.
+    pub fn measure_into_ring<D: DelayMs<u8>, const N: usize>(&mut self, cs: ClockStretch, rpt: Repeatability, delay: &mut D, ring: &mut MeasurementRing<N>) -> Result<(), Error<E>> {
+        ring.push(self.measure(cs, rpt, delay)?);
+        Ok(())
+    }
+
+    /// Take a measurement after discarding the first `discard` readings.
+    ///
+    /// Useful right after power-up or a reset, when the first readings can
+    /// be slightly off. Each discarded reading adds a full conversion time
+    /// ([`Repeatability`]) to the latency.
+    pub fn measure_warmed<D: DelayMs<u8>>(&mut self, cs: ClockStretch, rpt: Repeatability, discard: u8, delay: &mut D) -> Result<Measurement, Error<E>> {
+        for _ in 0..discard {
+            self.measure(cs, rpt, delay)?;
+        }
+        self.measure(cs, rpt, delay)
+    }
+
+    /// Take a measurement, retrying up to `max_retries` times on failure.
+    ///
+    /// embedded-hal doesn't tell transient bus errors (arbitration loss, a
+    /// momentary NACK) apart from permanent ones, so every error is retried.
+    /// The wait between attempts doubles from 1 ms up to 64 ms.
+    pub fn measure_robust<D: DelayMs<u8>>(&mut self, cs: ClockStretch, rpt: Repeatability, max_retries: u8, delay: &mut D) -> Result<Measurement, Error<E>> {
+        let mut attempt = 0;
+        loop {
+            match self.measure(cs, rpt, delay) {
+                Ok(m) => return Ok(m),
+                Err(e) if attempt >= max_retries => return Err(e),
+                Err(_) => {
+                    warn!("measurement failed, retrying");
+                    delay.delay_ms(1 << attempt.min(6));
+                    attempt += 1;
+                }
+            }
+        }
+    }
+
+    /// Take a measurement at low repeatability, escalating to medium and
+    /// then high if the data is corrupted, and return the first valid
+    /// reading with the repeatability it was taken at.
+    ///
+    /// Only [`Error::Crc`] and [`Error::ShortRead`] escalate; other errors
+    /// are returned right away. In the worst case this takes all three
+    /// conversions, 4 + 6 + 15 ms, instead of the 15 ms of a single high
+    /// repeatability measurement.
+    pub fn measure_escalating<D: DelayMs<u8>>(&mut self, cs: ClockStretch, delay: &mut D) -> Result<(Measurement, Repeatability), Error<E>> {
+        let mut result = Err(Error::Crc);
+        for rpt in [Repeatability::Low, Repeatability::Medium, Repeatability::High] {
+            result = self.measure(cs, rpt, delay).map(|m| (m, rpt));
+            match result {
+                Err(Error::Crc | Error::ShortRead) => {
+                    warn!("corrupted measurement, escalating repeatability");
+                }
+                _ => break,
+            }
+        }
+        result
+    }
+
+    /// Take measurements until two consecutive ones agree within
+    /// `tolerance_temp` centidegrees and `tolerance_rh` centi-percent, and
+    /// return the later one.
+    ///
+    /// At most `max_attempts` measurements are taken, but always at least
+    /// two, so this takes at least twice as long as [`measure`](Self::measure).
+    /// Returns [`Error::Unstable`] if no pair agreed.
+    pub fn measure_stable<D: DelayMs<u8>>(&mut self, cs: ClockStretch, rpt: Repeatability, tolerance_temp: i32, tolerance_rh: u16, max_attempts: u8, delay: &mut D) -> Result<Measurement, Error<E>> {
+        let mut prev = self.measure(cs, rpt, delay)?;
+        for _ in 1..max_attempts.max(2) {
+            let m = self.measure(cs, rpt, delay)?;
+            if m.approx_eq(&prev, tolerance_temp, tolerance_rh) {
+                return Ok(m);
+            }
+            prev = m;
+        }
+        Err(Error::Unstable)
+    }
+
+    /// Take a measurement, wait `settle_ms`, and return a second one.
+    ///
+    /// Meant for power-up or after moving the sensor to a different
+    /// environment, when the first reading still reflects where the sensor
+    /// came from: humidity in particular lags behind, by several seconds up
+    /// to minutes for large changes. The second reading is taken once the
+    /// sensor had `settle_ms` to approach the new conditions, so this takes
+    /// that long plus two measurements. With a `tolerance` of
+    /// `(centidegrees, centi-percent)` it also checks that the two readings
+    /// agree, i.e. that the sensor had already settled before the wait, and
+    /// returns [`Error::NotSettled`] otherwise.
+    pub fn measure_settled<D: DelayMs<u8>>(&mut self, cs: ClockStretch, rpt: Repeatability, settle_ms: u16, tolerance: Option<(i32, u16)>, delay: &mut D) -> Result<Measurement, Error<E>> {
+        let first = self.measure(cs, rpt, delay)?;
+        delay_ms_long(delay, settle_ms);
+        let second = self.measure(cs, rpt, delay)?;
+        match tolerance {
+            Some((temp, rh)) if !second.approx_eq(&first, temp, rh) => Err(Error::NotSettled),
+            _ => Ok(second),
+        }
+    }
+
+    /// Take a measurement and pair it with a timestamp from `clock`.
+    ///
+    /// `clock` is called once the reading has passed CRC validation, so it
+    /// isn't called at all if the measurement fails.
+    pub fn measure_at<D: DelayMs<u8>, T, F: FnOnce() -> T>(&mut self, cs: ClockStretch, rpt: Repeatability, delay: &mut D, clock: F) -> Result<TimedMeasurement<T>, Error<E>> {
+        let measurement = self.measure(cs, rpt, delay)?;
+        Ok(TimedMeasurement { measurement, timestamp: clock() })
+    }
+
+    /// Fill `out` with up to `n` timestamped measurements, taken back to back.
+    ///
+    /// `clock` is called once per successful reading, right after it passed
+    /// CRC validation, as in [`measure_at`](Self::measure_at), so it should
+    /// be cheap and monotonic. At most `out.len()` readings are taken.
+    /// Returns the number of readings stored; if a measurement fails, the
+    /// readings before it are kept and their count is returned with the
+    /// error.
+    pub fn measure_series<D: DelayMs<u8>, T, F: FnMut() -> T>(&mut self, n: usize, cs: ClockStretch, rpt: Repeatability, delay: &mut D, mut clock: F, out: &mut [TimedMeasurement<T>]) -> Result<usize, (usize, Error<E>)> {
+        let n = n.min(out.len());
+        for (i, slot) in out[..n].iter_mut().enumerate() {
+            *slot = self.measure_at(cs, rpt, delay, &mut clock).map_err(|e| (i, e))?;
+        }
+        Ok(n)
+    }
+
+    /// Start periodic data acquisition.
+    pub fn start_periodic<D: DelayMs<u8>>(&mut self, rate: Rate, rpt: Repeatability, delay: &mut D) -> Result<(), Error<E>> {
+        self.write_command(Command::Periodic(rate, rpt), delay)?;
+        self.mode = Mode::Periodic(rate, rpt);
+        Ok(())
+    }
+
+    /// Fetch the latest measurement taken in periodic mode.
+    ///
+    /// The sensor NACKs the read if no new measurement is available yet,
+    /// which is reported as an [`Error::I2c`].
+    pub fn fetch_data<D: DelayMs<u8>>(&mut self, delay: &mut D) -> Result<Measurement, Error<E>> {
+        if self.auto_recover_every != 0 {
+            self.fetches_since_check += 1;
+            if self.fetches_since_check >= self.auto_recover_every {
+                self.fetches_since_check = 0;
+                if self.check_and_recover(delay)? {
+                    warn!("sensor reset detected, periodic acquisition restarted");
+                }
+            }
+        }
+        self.command(Command::FetchData, delay, None)?;
+        self.read_measurement_hooked()
+    }
+
+    /// Fetch the result of the last completed periodic or ART conversion.
+    ///
+    /// This is [`fetch_data`](Self::fetch_data) under a name that makes the
+    /// intent clear: it never starts a new conversion and never sends a
+    /// single shot command, so it's always safe while periodic mode runs,
+    /// even with [`with_auto_break`](Self::with_auto_break) enabled. The
+    /// value may be up to one measurement interval old.
+    pub fn fetch_latest<D: DelayMs<u8>>(&mut self, delay: &mut D) -> Result<Measurement, Error<E>> {
+        self.fetch_data(delay)
+    }
+
+    /// Start periodic data acquisition with accelerated response time (ART).
+    ///
+    /// The sensor then measures at 4 Hz; stop it with
+    /// [`stop_periodic`](Self::stop_periodic) like regular periodic mode.
+    pub fn start_art<D: DelayMs<u8>>(&mut self, delay: &mut D) -> Result<(), Error<E>> {
+        self.write_command(Command::PeriodicWithART, delay)?;
+        self.mode = Mode::Art;
+        Ok(())
+    }
+
+    /// Fetch the latest measurement taken in ART mode.
+    ///
+    /// This is the same as [`fetch_data`](Self::fetch_data): ART frames have
+    /// the same layout as every other measurement, temperature word first,
+    /// then humidity, each followed by its CRC.
+    pub fn fetch_art_data<D: DelayMs<u8>>(&mut self, delay: &mut D) -> Result<Measurement, Error<E>> {
+        self.fetch_data(delay)
+    }
+
+    /// Fetch the latest measurement, tagged with [`Source::Art`] if the
+    /// driver started ART mode and [`Source::Periodic`] otherwise.
+    pub fn fetch_sourced<D: DelayMs<u8>>(&mut self, delay: &mut D) -> Result<SourcedMeasurement, Error<E>> {
+        let source = match self.mode {
+            Mode::Art => Source::Art,
+            _ => Source::Periodic,
+        };
+        let measurement = self.fetch_data(delay)?;
+        Ok(SourcedMeasurement { measurement, source })
+    }
+
+    /// Iterate over periodic or ART measurements.
+    ///
+    /// Each item waits for one measurement interval of the tracked mode
+    /// before fetching, see [`Rate::interval_ms`]. The iterator ends if the
+    /// driver isn't in periodic or ART mode, and borrows the driver and the
+    /// delay until it's dropped.
+    pub fn measurements<'a, D: DelayMs<u8>>(&'a mut self, delay: &'a mut D) -> Measurements<'a, I2C, C, D> {
+        Measurements { sht: self, delay }
+    }
+
+    /// Iterate over periodic or ART measurements with optional calibration
+    /// and smoothing, see [`ProcessedMeasurements`].
+    pub fn processed<'a, D: DelayMs<u8>>(&'a mut self, delay: &'a mut D, calibration: Option<Calibration>, ema: Option<Ema>) -> ProcessedMeasurements<'a, I2C, C, D> {
+        ProcessedMeasurements { inner: self.measurements(delay), calibration, ema }
+    }
+
+    /// Stop periodic data acquisition and return to single shot mode.
+    ///
+    /// Does nothing if the driver didn't start periodic acquisition.
+    pub fn stop_periodic<D: DelayMs<u8>>(&mut self, delay: &mut D) -> Result<(), Error<E>> {
+        if self.mode == Mode::SingleShot {
+            return Ok(());
+        }
+        self.stop_periodic_force(delay)
+    }
+
+    /// Issue a break command, regardless of the tracked mode.
+    fn stop_periodic_force<D: DelayMs<u8>>(&mut self, delay: &mut D) -> Result<(), Error<E>> {
+        self.write_command(Command::Break, delay)?;
+        self.mode = Mode::SingleShot;
+        Ok(())
+    }
+
+    /// Whether the driver last turned the heater on.
+    pub const fn heater(&self) -> bool {
+        self.heater
+    }
+
+    /// Turn the heater on.
+    ///
+    /// Does nothing if the driver already turned it on.
+    pub fn heater_enable<D: DelayMs<u8>>(&mut self, delay: &mut D) -> Result<(), Error<E>> {
+        if self.heater {
+            return Ok(());
+        }
+        self.write_command(Command::HeaterEnable, delay)?;
+        self.heater = true;
+        Ok(())
+    }
+
+    /// Turn the heater on for `duration_ms` milliseconds.
+    ///
+    /// The heater is turned off afterwards even if turning it on failed, so
+    /// it isn't left running by mistake; the first error is returned. A few
+    /// seconds are enough to raise the sensor temperature by several degrees
+    /// and drive off condensation. The heater draws 3.6 mW to 33 mW
+    /// depending on the supply voltage, and readings taken while it's on (or
+    /// shortly after) don't reflect the ambient conditions.
+    pub fn heater_pulse<D: DelayMs<u8>>(&mut self, duration_ms: u16, delay: &mut D) -> Result<(), Error<E>> {
+        let result = self.heater_enable(delay);
+        if result.is_ok() {
+            delay_ms_long(delay, duration_ms);
+        }
+
+        let disabled = self.heater_disable_force(delay);
+        result.and(disabled)
+    }
+
+    /// Turn the heater off.
+    ///
+    /// Does nothing if the heater is already off as far as the driver knows,
+    /// see [`heater_disable_force`](Self::heater_disable_force).
+    pub fn heater_disable<D: DelayMs<u8>>(&mut self, delay: &mut D) -> Result<(), Error<E>> {
+        if !self.heater {
+            return Ok(());
+        }
+        self.heater_disable_force(delay)
+    }
+
+    /// Turn the heater off, always issuing the command.
+    pub fn heater_disable_force<D: DelayMs<u8>>(&mut self, delay: &mut D) -> Result<(), Error<E>> {
+        self.write_command(Command::HeaterDisable, delay)?;
+        self.heater = false;
+        Ok(())
+    }
+
+    /// Check that the heater state the driver tracks matches the status
+    /// register's heater bit. Returns `false` on a mismatch.
+    ///
+    /// A mismatch means the two got out of sync, e.g. the sensor reset, or
+    /// another bus master changed the heater. To recover, call
+    /// [`heater_disable_force`](Self::heater_disable_force), which always
+    /// sends the command, and then [`heater_enable`](Self::heater_enable)
+    /// if the heater should be on.
+    pub fn heater_sanity_check<D: DelayMs<u8>>(&mut self, delay: &mut D) -> Result<bool, Error<E>> {
+        let on = self.status_raw(delay)? & status_bits::HEATER != 0;
+        Ok(on == self.heater)
+    }
+
+    /// Turn the heater off and take measurements until the humidity is back
+    /// within `tolerance_centi` centi-percent of `baseline`, a reading from
+    /// before heating, and return that measurement.
+    ///
+    /// Readings use the configured settings, see
+    /// [`with_settings`](Self::with_settings), and are taken every 500 ms.
+    /// `max_wait_ms` bounds the time spent waiting between readings, not
+    /// counting the conversions themselves; if the humidity still hasn't
+    /// recovered by then, this fails with [`Error::Unstable`].
+    pub fn wait_for_rh_recovery<D: DelayMs<u8>>(&mut self, baseline: &Measurement, tolerance_centi: u16, max_wait_ms: u16, delay: &mut D) -> Result<Measurement, Error<E>> {
+        const POLL_INTERVAL_MS: u16 = 500;
+
+        self.heater_disable(delay)?;
+        let mut waited = 0;
+        loop {
+            let m = self.measure(self.cs, self.rpt, delay)?;
+            if m.humidity.abs_diff(baseline.humidity) <= tolerance_centi {
+                return Ok(m);
+            }
+            if waited >= max_wait_ms {
+                return Err(Error::Unstable);
+            }
+            let step = POLL_INTERVAL_MS.min(max_wait_ms - waited);
+            delay_ms_long(delay, step);
+            waited += step;
+        }
+    }
+
+    /// Read and convert a measurement frame, and pass it to the hook set with
+    /// [`with_on_measurement`](Self::with_on_measurement).
+    fn read_measurement_hooked(&mut self) -> Result<Measurement, Error<E>> {
+        let frame = self.read_frame()?;
+        Ok(self.parse_hooked(&frame))
+    }
+
+    /// Read a measurement frame, checking the CRCs unless disabled.
+    fn read_frame(&mut self) -> Result<[u8; 6], Error<E>> {
+        let mut buf = [0; 6];
+        self.read_words(&mut buf)?;
+        self.validate_frame(&buf)?;
+        Ok(buf)
+    }
+
+    /// Read a response made up of 3-byte words (data plus CRC).
+    ///
+    /// The blocking `Read` trait can't report a partial transfer, so the
+    /// buffer is pre-filled with `0xFF`, the level an undriven bus reads as.
+    /// A word that is still all `0xFF` afterwards was never received and is
+    /// reported as [`Error::ShortRead`]. With the Sensirion CRC such a word
+    /// can't pass the CRC check anyway, so this never rejects valid data; it
+    /// only turns a confusing [`Error::Crc`] into a more precise error for
+    /// HALs (e.g. DMA-based ones) that return early without filling the
+    /// buffer. Some custom [`Crc`]s map `0xFFFF` to `0xFF`, which makes an
+    /// all-`0xFF` word valid; for those the check is skipped.
+    fn read_words(&mut self, buf: &mut [u8]) -> Result<(), Error<E>> {
+        buf.fill(0xFF);
+        if !self.dry_run_read(buf) {
+            self.i2c
+                .read(self.address, buf)
+                .map_err(|e| self.i2c_error(e))?;
+        }
+
+        check_short_read(buf, &self.crc)
+    }
+
+    /// Soft reset the sensor.
+    ///
+    /// This restarts the sensor with its power-up defaults: periodic mode
+    /// stops, the heater turns off and the alert limits go back to their
+    /// defaults. To only acknowledge an alert or a reset flag, use
+    /// [`clear_status`](Self::clear_status) instead.
+    pub fn reset<D: DelayMs<u8>>(&mut self, delay: &mut D) -> Result<(), Error<E>> {
+        self.command(Command::SoftReset, delay, Some(SOFT_RESET_TIME_MS))?;
+        self.mode = Mode::SingleShot;
+        self.heater = false;
+        self.alert_limits = None;
+        Ok(())
+    }
+
+    /// Soft reset the sensor, see [`reset`](Self::reset).
+    ///
+    /// The same command under a name that can't be mistaken for
+    /// [`clear_status`](Self::clear_status) in user code.
+    pub fn reset_soft_only<D: DelayMs<u8>>(&mut self, delay: &mut D) -> Result<(), Error<E>> {
+        self.reset(delay)
+    }
+
+    /// Put the sensor in the single shot idle state, e.g. before sleeping.
+    ///
+    /// Always issues a break and turns the heater off, regardless of the
+    /// tracked state, so the sensor ends up idle even if it was configured
+    /// elsewhere. It then draws its idle current (0.2 µA typical, Table 4).
+    /// The rest of the configuration, such as the alert limits, is kept.
+    pub fn enter_idle<D: DelayMs<u8>>(&mut self, delay: &mut D) -> Result<(), Error<E>> {
+        self.stop_periodic_force(delay)?;
+        self.heater_disable_force(delay)
+    }
+
+    /// Soft reset the sensor and restore the configuration the driver
+    /// tracks.
+    ///
+    /// Periodic acquisition is stopped first. After the reset the heater is
+    /// turned back on if it was on, the alert limits are rewritten if they
+    /// were last set with [`set_alert_limits`](Self::set_alert_limits) (limits
+    /// the driver doesn't know are left at the sensor's defaults), and
+    /// periodic or ART acquisition is restarted.
+    pub fn reset_preserving_config<D: DelayMs<u8>>(&mut self, delay: &mut D) -> Result<(), Error<E>> {
+        let (mode, heater, alert_limits) = (self.mode, self.heater, self.alert_limits);
+        self.stop_periodic(delay)?;
+        self.reset(delay)?;
+
+        if heater {
+            self.heater_enable(delay)?;
+        }
+        if let Some(limits) = alert_limits {
+            self.set_alert_limits(&limits, delay)?;
+        }
+        match mode {
+            Mode::Periodic(rate, rpt) => self.start_periodic(rate, rpt, delay),
+            Mode::Art => self.start_art(delay),
+            Mode::SingleShot => Ok(()),
+        }
+    }
+
+    /// Bring the sensor into the state described by `config`, optionally
+    /// starting periodic acquisition at `periodic` with the configured
+    /// repeatability.
+    ///
+    /// Stops periodic acquisition, resets the sensor, applies the settings
+    /// and calibration, writes the alert limits if there are any and starts
+    /// periodic acquisition, in that order. The driver's address stays as it
+    /// is, see [`from_config`](Sht3x::from_config). If any step fails, this
+    /// sends a break and a soft reset, ignoring their errors, so the sensor
+    /// isn't left half-configured, then returns the original error. That
+    /// is best-effort: if the bus itself is dead, the rollback fails too.
+    /// The driver's clock stretching, repeatability and calibration are
+    /// restored to what they were before the call, so
+    /// [`to_config`](Self::to_config) never reports a config that wasn't
+    /// fully applied; the tracked mode and alert limits are the reset
+    /// sensor's.
+    pub fn configure<D: DelayMs<u8>>(&mut self, config: &Config, periodic: Option<Rate>, delay: &mut D) -> Result<(), Error<E>> {
+        let (cs, rpt, calibration) = (self.cs, self.rpt, self.calibration);
+        let result = self.apply_config(config, periodic, delay);
+        if result.is_err() {
+            warn!("configuration failed, resetting sensor");
+            let _ = self.stop_periodic_force(delay);
+            let _ = self.reset(delay);
+            self.cs = cs;
+            self.rpt = rpt;
+            self.calibration = calibration;
+        }
+        result
+    }
+
+    fn apply_config<D: DelayMs<u8>>(&mut self, config: &Config, periodic: Option<Rate>, delay: &mut D) -> Result<(), Error<E>> {
+        self.stop_periodic(delay)?;
+        self.reset(delay)?;
+        self.cs = config.clock_stretch;
+        self.rpt = config.repeatability;
+        self.calibration = config.calibration;
+        if let Some(limits) = &config.alert_limits {
+            self.set_alert_limits(limits, delay)?;
+        }
+        if let Some(rate) = periodic {
+            self.start_periodic(rate, config.repeatability, delay)?;
+        }
+        Ok(())
+    }
+
+    /// Soft reset the sensor and confirm that the reset took effect.
+    ///
+    /// After the reset the status register is read back, which must have
+    /// `Status::SYSTEM_RESET_DETECTED` set; otherwise
+    /// [`Error::ResetNotDetected`] is returned. If the sensor doesn't respond
+    /// after the reset, the status read fails with [`Error::I2c`].
+    pub fn reset_and_verify<D: DelayMs<u8>>(&mut self, delay: &mut D) -> Result<(), Error<E>> {
+        self.reset(delay)?;
+        if self.status_raw(delay)? & status_bits::SYSTEM_RESET_DETECTED == 0 {
+            return Err(Error::ResetNotDetected);
+        }
+
+        Ok(())
+    }
+
+    /// Read the status register.
+    #[cfg(feature = "status-parse")]
+    pub fn status<D: DelayMs<u8>>(&mut self, delay: &mut D) -> Result<Status, Error<E>> {
+        self.status_raw(delay).map(Status::from_bits_truncate)
+    }
+
+    /// Read the status register without parsing it into a `Status`.
+    ///
+    /// The CRC is still checked. This is always available, while `status`
+    /// and the `Status` type need the `status-parse` feature (enabled by
+    /// default), so minimal builds can leave the parsing code out.
+    pub fn status_raw<D: DelayMs<u8>>(&mut self, delay: &mut D) -> Result<u16, Error<E>> {
+        self.command(Command::Status, delay, None)?;
+        let mut buf = [0; 3];
+        self.read_words(&mut buf)?;
+
+        let status = self.check_crc_counted([buf[0], buf[1]], buf[2])?;
+        self.last_status = Some(status);
+        Ok(status)
+    }
+
+    /// Whether the sensor executed the last command.
+    ///
+    /// Reads the status register's command status bit (bit 1), which the
+    /// sensor sets when it couldn't execute a command, e.g. because it was
+    /// corrupted in transit. The bit stays set until the next command
+    /// succeeds or the status is cleared.
+    pub fn last_command_ok<D: DelayMs<u8>>(&mut self, delay: &mut D) -> Result<bool, Error<E>> {
+        Ok(self.status_raw(delay)? & status_bits::COMMAND == 0)
+    }
+
+    /// Check whether the sensor responds, without changing its state.
+    ///
+    /// Issues a single status register read and returns `false` only if the
+    /// bus transaction failed (e.g. the address was NACKed). A response with
+    /// a bad CRC still counts as present.
+    pub fn is_present<D: DelayMs<u8>>(&mut self, delay: &mut D) -> bool {
+        !matches!(self.status_raw(delay), Err(Error::I2c(_)))
+    }
+
+    /// Read the electronic identification code (serial number).
+    pub fn serial_number<D: DelayMs<u8>>(&mut self, delay: &mut D) -> Result<u32, Error<E>> {
+        let buf = self.serial_number_raw(delay)?;
+        let high = self.check_crc_counted([buf[0], buf[1]], buf[2])?;
+        let low = self.check_crc_counted([buf[3], buf[4]], buf[5])?;
+        Ok(((high as u32) << 16) | low as u32)
+    }
+
+    /// Read the serial number response exactly as the sensor sent it, both
+    /// words with their CRC bytes, without checking the CRCs.
+    ///
+    /// Meant for diagnosing sensors that return bad serial numbers; use
+    /// [`serial_number`](Self::serial_number) otherwise.
+    pub fn serial_number_raw<D: DelayMs<u8>>(&mut self, delay: &mut D) -> Result<[u8; 6], Error<E>> {
+        self.command(Command::SerialNumber, delay, None)?;
+        let mut buf = [0; 6];
+        self.read_words(&mut buf)?;
+        Ok(buf)
+    }
+
+    /// Read the serial number, status register and one measurement.
+    ///
+    /// Intended for bring-up and bug reports, the result can be printed with
+    /// `{}`.
+    #[cfg(feature = "status-parse")]
+    pub fn diagnostics<D: DelayMs<u8>>(&mut self, delay: &mut D) -> Result<Diagnostics, Error<E>> {
+        let serial_number = self.serial_number(delay)?;
+        let status = self.status(delay)?;
+        let measurement = self.measure(ClockStretch::Disabled, Repeatability::High, delay)?;
+
+        Ok(Diagnostics { serial_number, status, measurement })
+    }
+
+    /// Clear the status register.
+    ///
+    /// This only clears the sticky alert and reset flags; it leaves the
+    /// mode, heater and alert limits alone, unlike [`reset`](Self::reset).
+    pub fn clear_status<D: DelayMs<u8>>(&mut self, delay: &mut D) -> Result<(), Error<E>> {
+        self.command(Command::ClearStatus, delay, None)
+    }
+
+    /// Read a single alert threshold.
+    pub fn read_alert_limit<D: DelayMs<u8>>(&mut self, threshold: AlertThreshold, delay: &mut D) -> Result<AlertLimit, Error<E>> {
+        self.command(Command::ReadAlertLimit(threshold), delay, None)?;
+        let mut buf = [0; 3];
+        self.read_words(&mut buf)?;
+
+        let word = self.check_crc_counted([buf[0], buf[1]], buf[2])?;
+        Ok(AlertLimit::from_raw(word))
+    }
+
+    /// Read all four alert thresholds.
+    pub fn read_alert_limits<D: DelayMs<u8>>(&mut self, delay: &mut D) -> Result<AlertLimits, Error<E>> {
+        Ok(AlertLimits {
+            high_set: self.read_alert_limit(AlertThreshold::HighSet, delay)?,
+            high_clear: self.read_alert_limit(AlertThreshold::HighClear, delay)?,
+            low_clear: self.read_alert_limit(AlertThreshold::LowClear, delay)?,
+            low_set: self.read_alert_limit(AlertThreshold::LowSet, delay)?,
+        })
+    }
+
+    /// Write a single alert threshold.
+    ///
+    /// The write includes a CRC, which the sensor checks. Afterwards the
+    /// status register is read back and [`Error::WriteChecksum`] returned if
+    /// `Status::WRITE_DATA_CHECKSUM` is set, meaning the sensor received a
+    /// corrupted limit and ignored it.
+    pub fn set_alert_limit<D: DelayMs<u8>>(&mut self, threshold: AlertThreshold, limit: AlertLimit, delay: &mut D) -> Result<(), Error<E>> {
+        self.command_with_data(Command::WriteAlertLimit(threshold), limit.to_raw(), delay)?;
+        let status = self.status_raw(delay)?;
+        if status & status_bits::WRITE_DATA_CHECKSUM != 0 {
+            return Err(Error::WriteChecksum);
+        }
+        if self.verify_commands && status & status_bits::COMMAND != 0 {
+            return Err(Error::CommandRejected);
+        }
+
+        if let Some(limits) = &mut self.alert_limits {
+            *limits.get_mut(threshold) = limit;
+        }
+        Ok(())
+    }
+
+    /// Write all four alert thresholds.
+    pub fn set_alert_limits<D: DelayMs<u8>>(&mut self, limits: &AlertLimits, delay: &mut D) -> Result<(), Error<E>> {
+        self.set_alert_limit(AlertThreshold::HighSet, limits.high_set, delay)?;
+        self.set_alert_limit(AlertThreshold::HighClear, limits.high_clear, delay)?;
+        self.set_alert_limit(AlertThreshold::LowClear, limits.low_clear, delay)?;
+        self.set_alert_limit(AlertThreshold::LowSet, limits.low_set, delay)?;
+        self.alert_limits = Some(*limits);
+        Ok(())
+    }
+
+    /// Write the alert thresholds of a preset, see [`AlertPreset`].
+    pub fn set_alert_preset<D: DelayMs<u8>>(&mut self, preset: AlertPreset, delay: &mut D) -> Result<(), Error<E>> {
+        self.set_alert_limits(&preset.limits(), delay)
+    }
+
+    /// Set the alert thresholds so that the ALERT pin never asserts.
+    ///
+    /// See [`AlertLimits::DISABLED`] for the values written.
+    pub fn disable_alerts<D: DelayMs<u8>>(&mut self, delay: &mut D) -> Result<(), Error<E>> {
+        self.set_alert_limits(&AlertLimits::DISABLED, delay)
+    }
+
+    /// Recover from an unexpected sensor reset.
+    ///
+    /// Reads the status register and, if a system reset was detected, clears
+    /// the status register and restarts periodic acquisition with the rate
+    /// and repeatability last passed to [`start_periodic`](Self::start_periodic).
+    /// Returns `true` if a reset was detected.
+    ///
+    /// This relies on the mode tracked by the driver, so it can only restore
+    /// modes configured through this driver instance.
+    pub fn check_and_recover<D: DelayMs<u8>>(&mut self, delay: &mut D) -> Result<bool, Error<E>> {
+        if self.status_raw(delay)? & status_bits::SYSTEM_RESET_DETECTED == 0 {
+            return Ok(false);
+        }
+
+        // The reset also turned the heater off and restored the default
+        // alert limits.
+        self.heater = false;
+        self.alert_limits = None;
+        self.clear_status(delay)?;
+        match self.mode {
+            Mode::Periodic(rate, rpt) => self.write_command(Command::Periodic(rate, rpt), delay)?,
+            Mode::Art => self.write_command(Command::PeriodicWithART, delay)?,
+            Mode::SingleShot => (),
+        }
+
+        Ok(true)
+    }
+}
+
+impl<I2C, E, C> Sht3x<I2C, C>
+where
+    I2C: WriteRead<Error = E>,
+    C: Conversion,
+{
+    /// Take a clock stretched measurement in a single write-read transaction,
+    /// without a delay.
+    ///
+    /// The sensor holds SCL low until the conversion is done, so this relies
+    /// on the HAL waiting through clock stretching for at least
+    /// [`MAX_CLOCK_STRETCH_US`](Self::MAX_CLOCK_STRETCH_US); with a shorter
+    /// stretch timeout the read fails. Only needs [`WriteRead`], and doesn't
+    /// issue a break even with [`with_auto_break`](Self::with_auto_break).
+    pub fn measure_clock_stretch(&mut self, rpt: Repeatability) -> Result<Measurement, Error<E>> {
+        let command = Command::SingleShot(ClockStretch::Enabled, rpt);
+        trace!("command {:#06x} to {:#04x}", command.value(), self.address);
+
+        // See `read_words` for the pre-fill.
+        let mut buf = [0xFF; 6];
+        if self.dry_run_command(command) {
+            self.dry_run_read(&mut buf);
+        } else {
+            self.i2c
+                .write_read(self.address, &command.value().to_be_bytes(), &mut buf)
+                .map_err(|e| self.i2c_error(e))?;
+        }
+        self.clock_stretch = true;
+        check_short_read(&buf, &self.crc)?;
+        self.validate_frame(&buf)?;
+        Ok(self.parse_hooked(&buf))
+    }
+
+    /// Read the status register in a single write-read transaction.
+    ///
+    /// The sensor answers the status command right away, so unlike
+    /// [`status_raw`](Sht3x::status_raw) this needs neither a delay nor
+    /// separate [`Read`] and [`Write`] support. Together with
+    /// [`measure_clock_stretch`](Self::measure_clock_stretch) it covers HALs
+    /// that only implement [`WriteRead`]. Measurements without clock
+    /// stretching can't be done this way: the sensor NACKs the read until
+    /// the conversion is done, which needs a delay between the two.
+    pub fn status_write_read(&mut self) -> Result<u16, Error<E>> {
+        let command = Command::Status;
+        trace!("command {:#06x} to {:#04x}", command.value(), self.address);
+
+        let mut buf = [0xFF; 3];
+        if self.dry_run_command(command) {
+            self.dry_run_read(&mut buf);
+        } else {
+            self.i2c
+                .write_read(self.address, &command.value().to_be_bytes(), &mut buf)
+                .map_err(|e| self.i2c_error(e))?;
+        }
+        check_short_read(&buf, &self.crc)?;
+        let status = self.check_crc_counted([buf[0], buf[1]], buf[2])?;
+        self.last_status = Some(status);
+        Ok(status)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<I2C, C> Sht3x<I2C, C>
+where
+    I2C: embedded_hal_async::i2c::I2c,
+    C: Conversion,
+{
+    /// Send an I2C command, awaiting the bus and the wait time.
+    async fn command_async<D: DelayNs>(&mut self, command: Command, delay: &mut D, wait_time: Option<u8>) -> Result<(), Error<I2C::Error>> {
+        trace!("command {:#06x} to {:#04x}", command.value(), self.address);
+        if !self.dry_run_command(command) {
+            self.i2c
+                .write(self.address, &command.value().to_be_bytes())
+                .await
+                .map_err(|e| self.i2c_error(e))?;
+        }
+
+        delay.delay_ms(wait_time.unwrap_or(0).max(COMMAND_WAIT_TIME_MS).into()).await;
+
+        Ok(())
+    }
+
+    /// Send a configuration command without blocking, verifying it if
+    /// enabled with [`with_verify_commands`](Self::with_verify_commands).
+    async fn write_command_async<D: DelayNs>(&mut self, command: Command, delay: &mut D) -> Result<(), Error<I2C::Error>> {
+        self.command_async(command, delay, None).await?;
+        if self.verify_commands && self.status_raw_async(delay).await? & status_bits::COMMAND != 0 {
+            return Err(Error::CommandRejected);
+        }
+        Ok(())
+    }
+
+    /// Read the status register without blocking, see
+    /// [`status_raw`](Sht3x::status_raw).
+    pub async fn status_raw_async<D: DelayNs>(&mut self, delay: &mut D) -> Result<u16, Error<I2C::Error>> {
+        self.command_async(Command::Status, delay, None).await?;
+        // See `read_words` for the pre-fill.
+        let mut buf = [0xFF; 3];
+        if !self.dry_run_read(&mut buf) {
+            self.i2c
+                .read(self.address, &mut buf)
+                .await
+                .map_err(|e| self.i2c_error(e))?;
+        }
+        check_short_read(&buf, &self.crc)?;
+        let status = self.check_crc_counted([buf[0], buf[1]], buf[2])?;
+        self.last_status = Some(status);
+        Ok(status)
+    }
+
+    /// Take a temperature and humidity measurement without blocking.
+    pub async fn measure_async<D: DelayNs>(&mut self, cs: ClockStretch, rpt: Repeatability, delay: &mut D) -> Result<Measurement, Error<I2C::Error>> {
+        if self.auto_break {
+            self.write_command_async(Command::Break, delay).await?;
+            self.mode = Mode::SingleShot;
+        }
+        let wait = self.conversion_delay(rpt);
+        self.command_async(Command::SingleShot(cs, rpt), delay, Some(wait)).await?;
+        self.clock_stretch = cs == ClockStretch::Enabled;
+        self.read_measurement_async().await
+    }
+
+    /// Read and convert a measurement frame without blocking.
+    async fn read_measurement_async(&mut self) -> Result<Measurement, Error<I2C::Error>> {
+        // See `read_words` for the pre-fill.
+        let mut buf = [0xFF; 6];
+        if !self.dry_run_read(&mut buf) {
+            self.i2c
+                .read(self.address, &mut buf)
+                .await
+                .map_err(|e| self.i2c_error(e))?;
+        }
+        check_short_read(&buf, &self.crc)?;
+        self.validate_frame(&buf)?;
+        Ok(self.parse_hooked(&buf))
+    }
+
+    /// Start periodic data acquisition without blocking, see
+    /// [`start_periodic`](Self::start_periodic).
+    pub async fn start_periodic_async<D: DelayNs>(&mut self, rate: Rate, rpt: Repeatability, delay: &mut D) -> Result<(), Error<I2C::Error>> {
+        self.write_command_async(Command::Periodic(rate, rpt), delay).await?;
+        self.mode = Mode::Periodic(rate, rpt);
+        Ok(())
+    }
+
+    /// Fetch the latest periodic measurement without blocking, see
+    /// [`fetch_data`](Self::fetch_data).
+    pub async fn fetch_data_async<D: DelayNs>(&mut self, delay: &mut D) -> Result<Measurement, Error<I2C::Error>> {
+        self.command_async(Command::FetchData, delay, None).await?;
+        self.read_measurement_async().await
+    }
+
+    /// Collect `N` periodic measurements without blocking the executor.
+    ///
+    /// Before each fetch this awaits `interval_ms`, which should be at least
+    /// the [`Rate::interval_ms`] periodic mode was started with, or the
+    /// sensor has no new data. Each fetch takes another 1 ms or so for the
+    /// command. Errors don't stop the batch: every slot holds the result of
+    /// its own fetch, e.g. [`Error::I2c`] if the sensor NACKed it.
+    pub async fn fetch_batch<const N: usize, D: DelayNs>(&mut self, interval_ms: u32, delay: &mut D) -> [Result<Measurement, Error<I2C::Error>>; N] {
+        // Every slot is overwritten below.
+        let mut out = core::array::from_fn(|_| Err(Error::ShortRead));
+        for slot in out.iter_mut() {
+            delay.delay_ms(interval_ms).await;
+            *slot = self.fetch_data_async(delay).await;
+        }
+        out
+    }
+
+    /// Stream periodic or ART measurements, the async counterpart of
+    /// [`measurements`](Self::measurements).
+    ///
+    /// Each item awaits one measurement interval of the tracked mode and then
+    /// fetches, so there's no buffering: a consumer that polls less often
+    /// than the sensor measures just gets the latest value, and the ones in
+    /// between are lost. The stream ends if the driver isn't in periodic or
+    /// ART mode, and borrows the driver and the delay until it's dropped.
+    pub fn measurement_stream<'a, D: DelayNs>(&'a mut self, delay: &'a mut D) -> impl futures_core::Stream<Item = Result<Measurement, Error<I2C::Error>>> + 'a {
+        MeasurementStream {
+            state: Some((self, delay)),
+            step: |(sht, delay): (&'a mut Self, &'a mut D)| async move {
+                let interval = match sht.mode {
+                    Mode::Periodic(rate, _) => rate.interval_ms(),
+                    Mode::Art => Rate::R4.interval_ms(),
+                    Mode::SingleShot => return None,
+                };
+                delay.delay_ms(interval.into()).await;
+                let result = sht.fetch_data_async(delay).await;
+                Some((result, (sht, delay)))
+            },
+            pending: None,
+        }
+    }
+
+    /// Take a measurement, retrying up to `retries` times on corrupted data.
+    ///
+    /// Like [`measure_robust`](Self::measure_robust), the wait between
+    /// attempts doubles from 1 ms up to 64 ms, but it's awaited so other
+    /// tasks can run. Only [`Error::Crc`] and [`Error::ShortRead`] are
+    /// retried; bus errors are returned immediately.
+    pub async fn measure_retry<D: DelayNs>(&mut self, cs: ClockStretch, rpt: Repeatability, retries: u8, delay: &mut D) -> Result<Measurement, Error<I2C::Error>> {
+        let mut attempt = 0;
+        loop {
+            match self.measure_async(cs, rpt, delay).await {
+                Err(Error::Crc | Error::ShortRead) if attempt < retries => {
+                    warn!("corrupted measurement, retrying");
+                    delay.delay_ms(1 << attempt.min(6)).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+pin_project_lite::pin_project! {
+    /// Stream that runs `step` on its state for every item, see
+    /// [`Sht3x::measurement_stream`]
+    struct MeasurementStream<S, F, Fut> {
+        state: Option<S>,
+        step: F,
+        #[pin]
+        pending: Option<Fut>,
+    }
+}
+
+#[cfg(feature = "async")]
+impl<S, T, F, Fut> futures_core::Stream for MeasurementStream<S, F, Fut>
+where
+    F: FnMut(S) -> Fut,
+    Fut: core::future::Future<Output = Option<(T, S)>>,
+{
+    type Item = T;
+
+    fn poll_next(self: core::pin::Pin<&mut Self>, cx: &mut core::task::Context<'_>) -> core::task::Poll<Option<T>> {
+        use core::task::Poll;
+
+        let mut this = self.project();
+        if this.pending.is_none() {
+            match this.state.take() {
+                Some(state) => this.pending.set(Some((this.step)(state))),
+                None => return Poll::Ready(None),
+            }
+        }
+        let Some(pending) = this.pending.as_mut().as_pin_mut() else {
+            return Poll::Ready(None);
+        };
+        match pending.poll(cx) {
+            Poll::Ready(next) => {
+                this.pending.set(None);
+                Poll::Ready(next.map(|(item, state)| {
+                    *this.state = Some(state);
+                    item
+                }))
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+/// Driver settings that can be saved and restored across reboots
+///
+/// Everything except the alert limits lives in the driver, see
+/// [`Sht3x::from_config`] and [`Sht3x::to_config`]. With the `serde` feature
+/// it implements `Serialize` and `Deserialize`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Config {
+    /// 7-bit I2C address
+    pub address: u8,
+    pub clock_stretch: ClockStretch,
+    pub repeatability: Repeatability,
+    pub calibration: Calibration,
+    /// Alert limits to write to the sensor, if any
+    pub alert_limits: Option<AlertLimits>,
+}
+
+/// Snapshot of the state tracked by the driver, see [`Sht3x::debug_state`]
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DriverState {
+    /// 7-bit I2C address
+    pub address: u8,
+    /// See [`Sht3x::mode`]
+    pub mode: Mode,
+    /// Whether the driver last turned the heater on
+    pub heater: bool,
+    /// See [`Sht3x::last_used_clock_stretch`]
+    pub last_used_clock_stretch: bool,
+    /// See [`Sht3x::read_count`]
+    pub read_count: u32,
+    /// The alert limits last written through the driver, if any
+    pub alert_limits: Option<AlertLimits>,
+}
+
+/// Iterator over periodic measurements, see [`Sht3x::measurements`]
+pub struct Measurements<'a, I2C, C, D> {
+    sht: &'a mut Sht3x<I2C, C>,
+    delay: &'a mut D,
+}
+
+impl<I2C, E, C, D> Iterator for Measurements<'_, I2C, C, D>
+where
+    I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+    C: Conversion,
+    D: DelayMs<u8>,
+{
+    type Item = Result<Measurement, Error<E>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let interval = match self.sht.mode {
+            Mode::Periodic(rate, _) => rate.interval_ms(),
+            Mode::Art => Rate::R4.interval_ms(),
+            Mode::SingleShot => return None,
+        };
+        delay_ms_long(self.delay, interval);
+        Some(self.sht.fetch_data(self.delay))
+    }
+}
+
+/// Periodic measurements with optional calibration and smoothing, see
+/// [`Sht3x::processed`]
+///
+/// Each reading is calibrated first and then fed to the moving average.
+/// Errors are passed through without touching the average.
+pub struct ProcessedMeasurements<'a, I2C, C, D> {
+    inner: Measurements<'a, I2C, C, D>,
+    calibration: Option<Calibration>,
+    ema: Option<Ema>,
+}
+
+impl<I2C, E, C, D> Iterator for ProcessedMeasurements<'_, I2C, C, D>
+where
+    I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+    C: Conversion,
+    D: DelayMs<u8>,
+{
+    type Item = Result<Measurement, Error<E>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.inner.next()?.map(|mut m| {
+            if let Some(calibration) = &self.calibration {
+                m = calibration.apply(&m);
+            }
+            if let Some(ema) = &mut self.ema {
+                m = ema.update(&m);
+            }
+            m
+        }))
+    }
+}
+
+/// Object-safe interface for temperature/humidity sensors
+///
+/// The method takes no generic parameters, so implementors have to store
+/// anything a measurement needs, such as a delay provider. This allows
+/// storing different sensors as `dyn TempHumiditySensor<Error = E>`.
+pub trait TempHumiditySensor {
+    type Error;
+
+    /// Take a temperature and humidity measurement.
+    fn read(&mut self) -> Result<Measurement, Self::Error>;
+}
+
+/// Driver bundled with a delay and measurement settings, implementing
+/// [`TempHumiditySensor`]
+#[derive(Debug, Clone)]
+pub struct Sht3xSensor<I2C, D, C = SensirionConversion> {
+    sht: Sht3x<I2C, C>,
+    delay: D,
+    cs: ClockStretch,
+    rpt: Repeatability,
+}
+
+impl<I2C, D, C> Sht3xSensor<I2C, D, C> {
+    /// Measure with the given settings on every [`read`](TempHumiditySensor::read).
+    pub const fn new(sht: Sht3x<I2C, C>, delay: D, cs: ClockStretch, rpt: Repeatability) -> Self {
+        Self { sht, delay, cs, rpt }
+    }
+
+    /// Give back the driver and delay.
+    pub fn release(self) -> (Sht3x<I2C, C>, D) {
+        (self.sht, self.delay)
+    }
+}
+
+impl<I2C, D, E, C> TempHumiditySensor for Sht3xSensor<I2C, D, C>
+where
+    I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+    D: DelayMs<u8>,
+    C: Conversion,
+{
+    type Error = Error<E>;
+
+    fn read(&mut self) -> Result<Measurement, Self::Error> {
+        self.sht.measure(self.cs, self.rpt, &mut self.delay)
+    }
+}
+
+/// Measure with the sensors at both addresses on the same bus.
+///
+/// Sensors are read one after the other, low address first. Each sensor gets
+/// its own result, so a missing or failing sensor doesn't hide the reading
+/// of the other one.
+pub fn measure_both<I2C, D, E>(
+    i2c: &mut I2C,
+    cs: ClockStretch,
+    rpt: Repeatability,
+    delay: &mut D,
+) -> (Result<Measurement, Error<E>>, Result<Measurement, Error<E>>)
+where
+    I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+    D: DelayMs<u8>,
+{
+    let low = Sht3x::new(BusRef(&mut *i2c), Address::Low).measure(cs, rpt, delay);
+    let high = Sht3x::new(BusRef(i2c), Address::High).measure(cs, rpt, delay);
+    (low, high)
+}
+
+/// Start periodic acquisition with the sensors at both addresses on the
+/// same bus, low address first.
+///
+/// The two start commands go out back to back, so the sensors' sampling
+/// starts at most one command (plus the 1 ms command wait) apart. From then
+/// on each sensor runs on its own oscillator, so the offset slowly drifts;
+/// call this again to re-align them. Read the results with [`fetch_both`].
+pub fn start_periodic_both<I2C, D, E>(
+    i2c: &mut I2C,
+    rate: Rate,
+    rpt: Repeatability,
+    delay: &mut D,
+) -> (Result<(), Error<E>>, Result<(), Error<E>>)
+where
+    I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+    D: DelayMs<u8>,
+{
+    let low = Sht3x::new(BusRef(&mut *i2c), Address::Low).start_periodic(rate, rpt, delay);
+    let high = Sht3x::new(BusRef(i2c), Address::High).start_periodic(rate, rpt, delay);
+    (low, high)
+}
+
+/// Fetch the latest periodic measurement from the sensors at both addresses,
+/// low address first.
+///
+/// Once per interval of the rate passed to [`start_periodic_both`], this
+/// returns a pair of samples taken about as far apart as the two sensors
+/// were started. Each sensor gets its own result, like [`measure_both`]: a
+/// sensor with no new measurement yet NACKs its fetch, reported as an
+/// [`Error::I2c`] for that sensor only, and can be fetched again without
+/// re-reading the other one.
+pub fn fetch_both<I2C, D, E>(i2c: &mut I2C, delay: &mut D) -> (Result<Measurement, Error<E>>, Result<Measurement, Error<E>>)
+where
+    I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+    D: DelayMs<u8>,
+{
+    let low = Sht3x::new(BusRef(&mut *i2c), Address::Low).fetch_data(delay);
+    let high = Sht3x::new(BusRef(i2c), Address::High).fetch_data(delay);
+    (low, high)
+}
+
+/// Whether condensation became imminent between two readings.
+///
+/// True if the dew point spread ([`Measurement::dew_point_spread_celsius`])
+/// fell from above 0.5 °C in `previous` to 0.5 °C or less in `current`, so
+/// an alarm fires once, as the surface approaches the dew point, and not on
+/// every reading while it stays there. The margin is about the sensor's
+/// combined temperature and humidity accuracy.
+#[cfg(feature = "float")]
+pub fn crosses_condensation(previous: &Measurement, current: &Measurement) -> bool {
+    const ONSET_SPREAD: f32 = 0.5;
+    previous.dew_point_spread_celsius() > ONSET_SPREAD && current.dew_point_spread_celsius() <= ONSET_SPREAD
+}
+
+/// Estimated milliseconds until the humidity reaches `threshold_rh_centi`,
+/// from readings `m1` and `m2` taken `dt_ms` apart.
+///
+/// Assumes the humidity keeps changing at the rate between `m1` and `m2`.
+/// Real humidity rarely does for long: the reading's noise dominates short
+/// intervals and the trend flattens as the air approaches equilibrium, so
+/// treat the result as a rough lead time and recompute it with every new
+/// reading. Returns `Some(0)` if `m2` is at the threshold, and `None` if the
+/// humidity is steady, moving away from the threshold or already past it,
+/// or if `dt_ms` is 0. Saturates at `u32::MAX`.
+pub fn time_to_threshold(m1: &Measurement, m2: &Measurement, dt_ms: u32, threshold_rh_centi: u16) -> Option<u32> {
+    let delta = i64::from(m2.humidity) - i64::from(m1.humidity);
+    let remaining = i64::from(threshold_rh_centi) - i64::from(m2.humidity);
+    if remaining == 0 {
+        return Some(0);
+    }
+    if dt_ms == 0 || delta == 0 || (delta < 0) != (remaining < 0) {
+        return None;
+    }
+    let ms = remaining * i64::from(dt_ms) / delta;
+    Some(u32::try_from(ms).unwrap_or(u32::MAX))
+}
+
+/// Find a sensor on the bus, trying the low address first.
+///
+/// A sensor counts as found if it returns its status register with a valid
+/// CRC. Pass the result to [`Sht3x::new_probed`]. Returns `None` if neither
+/// address answered.
+pub fn detect<I2C, D, E>(i2c: &mut I2C, delay: &mut D) -> Option<VerifiedAddress>
+where
+    I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+    D: DelayMs<u8>,
+{
+    [Address::Low, Address::High]
+        .into_iter()
+        .find(|&address| Sht3x::new(BusRef(&mut *i2c), address).status_raw(delay).is_ok())
+        .map(VerifiedAddress)
+}
+
+/// An address a sensor answered at, see [`detect`]
+#[derive(Debug, Copy, Clone)]
+pub struct VerifiedAddress(Address);
+
+impl VerifiedAddress {
+    /// The address the sensor answered at.
+    pub const fn address(&self) -> Address {
+        self.0
+    }
+}
+
+/// Sensors behind an I2C mux (e.g. a TCA9548A), read one channel at a time
+///
+/// `select` is called with the bus and a channel before each sensor is
+/// read, and is responsible for the mux write itself. All sensors use the
+/// same address, CRC parameters and conversion.
+pub struct MuxedSht3x<I2C, F, const N: usize, C = SensirionConversion> {
+    i2c: I2C,
+    address: Address,
+    channels: [u8; N],
+    select: F,
+    crc: Crc,
+    verify_crc: bool,
+    conversion: PhantomData<C>,
+}
+
+impl<I2C, F, E, const N: usize> MuxedSht3x<I2C, F, N>
+where
+    I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+    F: FnMut(&mut I2C, u8) -> Result<(), E>,
+{
+    /// Read the sensors at `address` on each of `channels`.
+    pub const fn new(i2c: I2C, address: Address, channels: [u8; N], select: F) -> Self {
+        Self { i2c, address, channels, select, crc: Crc::SENSIRION, verify_crc: true, conversion: PhantomData }
+    }
+}
+
+impl<I2C, F, const N: usize, C> MuxedSht3x<I2C, F, N, C> {
+    /// Use non-standard CRC parameters for every sensor, see
+    /// [`Sht3x::with_crc`].
+    pub fn with_crc(mut self, crc: Crc) -> Self {
+        self.crc = crc;
+        self
+    }
+
+    /// Enable or disable CRC validation for every sensor, see
+    /// [`Sht3x::with_verify_crc`].
+    pub fn with_verify_crc(mut self, enabled: bool) -> Self {
+        self.verify_crc = enabled;
+        self
+    }
+
+    /// Use a custom conversion for every sensor, see
+    /// [`Sht3x::with_conversion`].
+    pub fn with_conversion<C2: Conversion>(self) -> MuxedSht3x<I2C, F, N, C2> {
+        MuxedSht3x {
+            i2c: self.i2c,
+            address: self.address,
+            channels: self.channels,
+            select: self.select,
+            crc: self.crc,
+            verify_crc: self.verify_crc,
+            conversion: PhantomData,
+        }
+    }
+}
+
+impl<I2C, F, E, const N: usize, C> MuxedSht3x<I2C, F, N, C>
+where
+    I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+    F: FnMut(&mut I2C, u8) -> Result<(), E>,
+    C: Conversion,
+{
+    /// Measure with every sensor, in channel order.
+    ///
+    /// Each channel gets its own result; a failed channel select is
+    /// reported as that channel's [`Error::I2c`].
+    pub fn read_all<D: DelayMs<u8>>(&mut self, cs: ClockStretch, rpt: Repeatability, delay: &mut D) -> [Result<Measurement, Error<E>>; N] {
+        let channels = self.channels;
+        channels.map(|channel| {
+            (self.select)(&mut self.i2c, channel).map_err(Error::I2c)?;
+            Sht3x::new(BusRef(&mut self.i2c), self.address)
+                .with_crc(self.crc)
+                .with_verify_crc(self.verify_crc)
+                .with_conversion::<C>()
+                .measure(cs, rpt, delay)
+        })
+    }
+
+    /// Give back the bus and the channel select function.
+    pub fn release(self) -> (I2C, F) {
+        (self.i2c, self.select)
+    }
+}
+
+/// Check that SDA is released while the bus is idle.
+///
+/// A slave that was interrupted mid-transfer (e.g. by a brown-out or noise
+/// on a long cable) can hold SDA low indefinitely, failing every following
+/// transaction. Call this between transactions with `sda` read as a GPIO
+/// input; it returns [`Error::BusStuck`] if SDA is low. Pin errors are
+/// returned as [`Error::Pin`].
+#[cfg(feature = "bus-recovery")]
+pub fn check_bus<SDA: InputPin>(sda: &SDA) -> Result<(), Error<SDA::Error>> {
+    if sda.is_low().map_err(Error::Pin)? {
+        return Err(Error::BusStuck);
+    }
+    Ok(())
+}
+
+/// Try to free a bus stuck by a slave holding SDA low.
+///
+/// Clocks SCL up to 9 times at about 100 kHz until the slave releases SDA,
+/// then generates a STOP condition to reset the slaves' state, as described
+/// in UM10204 §3.1.16, and checks the bus with [`check_bus`]. This needs
+/// both pins as GPIOs, so the I2C peripheral has to release them first:
+/// `scl` and `sda` both as open-drain outputs, with `sda` read back as an
+/// input. Recovery is best-effort; a slave that keeps holding SDA still
+/// fails with [`Error::BusStuck`], and only a power cycle can help then.
+/// Pin errors are returned as [`Error::Pin`].
+#[cfg(feature = "bus-recovery")]
+pub fn recover_bus<SCL, SDA, D, E>(scl: &mut SCL, sda: &mut SDA, delay: &mut D) -> Result<(), Error<E>>
+where
+    SCL: OutputPin<Error = E>,
+    SDA: InputPin<Error = E> + OutputPin<Error = E>,
+    D: DelayUs<u8>,
+{
+    for _ in 0..9 {
+        if sda.is_high().map_err(Error::Pin)? {
+            break;
+        }
+        scl.set_low().map_err(Error::Pin)?;
+        delay.delay_us(5);
+        scl.set_high().map_err(Error::Pin)?;
+        delay.delay_us(5);
+    }
+    // STOP: SDA rises while SCL is high.
+    scl.set_low().map_err(Error::Pin)?;
+    delay.delay_us(5);
+    sda.set_low().map_err(Error::Pin)?;
+    delay.delay_us(5);
+    scl.set_high().map_err(Error::Pin)?;
+    delay.delay_us(5);
+    sda.set_high().map_err(Error::Pin)?;
+    delay.delay_us(5);
+    check_bus(sda)
+}
+
+/// Number of commands [`Sht3x::dry_run_log`] holds.
+#[cfg(feature = "dry-run")]
+const DRY_RUN_LOG_LEN: usize = 32;
+
+/// Dry-run state, see [`Sht3x::with_dry_run`].
+#[cfg(feature = "dry-run")]
+#[derive(Debug, Copy, Clone)]
+struct DryRun {
+    measurement: Measurement,
+    log: [u16; DRY_RUN_LOG_LEN],
+    len: usize,
+    /// Whether the last command produces a measurement frame.
+    measuring: bool,
+}
+
+#[cfg(feature = "dry-run")]
+impl DryRun {
+    fn record(&mut self, command: Command) {
+        if self.len < DRY_RUN_LOG_LEN {
+            self.log[self.len] = command.value();
+            self.len += 1;
+        }
+        self.measuring = matches!(command, Command::SingleShot(..) | Command::FetchData);
+    }
+
+    fn respond(&self, buf: &mut [u8], crc: &Crc) {
+        let words = if self.measuring {
+            [temperature_to_raw(self.measurement.temperature), humidity_to_raw(self.measurement.humidity)]
+        } else {
+            [0, 0]
+        };
+        for (chunk, word) in buf.chunks_exact_mut(3).zip(words) {
+            let [hi, lo] = word.to_be_bytes();
+            chunk.copy_from_slice(&[hi, lo, crc.checksum([hi, lo])]);
+        }
+    }
+}
+
+/// A bus that does nothing, for drivers in dry-run mode
+///
+/// Drivers built with [`Sht3x::with_dry_run`] never use their bus, so this
+/// stands in for a real one in tests. Without dry-run mode every read
+/// through it fails with [`Error::ShortRead`].
+#[cfg(feature = "dry-run")]
+#[derive(Debug, Copy, Clone, Default)]
+pub struct NoBus;
+
+#[cfg(feature = "dry-run")]
+impl Read for NoBus {
+    type Error = core::convert::Infallible;
+
+    fn read(&mut self, _address: u8, _buffer: &mut [u8]) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "dry-run")]
+impl Write for NoBus {
+    type Error = core::convert::Infallible;
+
+    fn write(&mut self, _address: u8, _bytes: &[u8]) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "dry-run")]
+impl WriteRead for NoBus {
+    type Error = core::convert::Infallible;
+
+    fn write_read(&mut self, _address: u8, _bytes: &[u8], _buffer: &mut [u8]) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// Borrowed bus, so temporary drivers can share it.
+struct BusRef<'a, I2C>(&'a mut I2C);
+
+impl<I2C: Read> Read for BusRef<'_, I2C> {
+    type Error = I2C::Error;
+
+    fn read(&mut self, address: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.0.read(address, buffer)
+    }
+}
+
+impl<I2C: Write> Write for BusRef<'_, I2C> {
+    type Error = I2C::Error;
+
+    fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.0.write(address, bytes)
+    }
+}
+
+impl<I2C: WriteRead> WriteRead for BusRef<'_, I2C> {
+    type Error = I2C::Error;
+
+    fn write_read(&mut self, address: u8, bytes: &[u8], buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.0.write_read(address, bytes, buffer)
+    }
+}
+
+/// Worst-case time for a single shot measurement at `rpt` repeatability on
+/// an `i2c_hz` bus, in microseconds.
+///
+/// This is the maximum conversion time plus the bus time of the command
+/// write (address and 2 command bytes) and the read (address and 6 data
+/// bytes). Every byte takes 9 clock cycles including its ACK, and each
+/// transaction adds one cycle each for the start and stop conditions. HAL
+/// overhead between transactions isn't included.
+pub const fn measure_worst_case_us(rpt: Repeatability, i2c_hz: u32) -> u32 {
+    const BUS_CYCLES: u64 = (3 * 9 + 2) + (7 * 9 + 2);
+    if i2c_hz == 0 {
+        return u32::MAX;
+    }
+    let bus_us = (BUS_CYCLES * 1_000_000).div_ceil(i2c_hz as u64) as u32;
+    rpt.max_duration() as u32 * 1000 + bus_us
+}
+
+/// Typical energy per hour of `reads_per_hour` single shot measurements, in
+/// microjoules.
+///
+/// Uses the same assumptions as [`Repeatability::energy_uj`], but without
+/// rounding each measurement. The idle current between measurements
+/// (0.2 µA typical, about 2.4 mJ per hour at 3.3 V) isn't included.
+pub const fn schedule_energy_uj(rpt: Repeatability, reads_per_hour: u32) -> u32 {
+    let uj = rpt.energy_nj() as u64 * reads_per_hour as u64 / 1000;
+    if uj > u32::MAX as u64 {
+        u32::MAX
+    } else {
+        uj as u32
+    }
+}
+
+/// Parse a raw 6-byte measurement frame, as sent by the sensor.
+///
+/// This validates both CRCs and converts the readings without needing a
+/// driver instance, e.g. for frames captured elsewhere.
+pub fn parse_frame(frame: &[u8; 6]) -> Result<Measurement, CrcError> {
+    parse_frame_with::<SensirionConversion>(frame, &Crc::SENSIRION)
+}
+
+/// Build the 6-byte frame a sensor sends for the raw readings `temp_raw`
+/// and `rh_raw`, with valid CRCs.
+///
+/// The inverse of [`parse_frame`], for test harnesses and sensor emulators
+/// that need to generate responses the driver accepts.
+pub const fn expected_frame(temp_raw: u16, rh_raw: u16) -> [u8; 6] {
+    let [t0, t1] = temp_raw.to_be_bytes();
+    let [h0, h1] = rh_raw.to_be_bytes();
+    [
+        t0, t1, Crc::SENSIRION.checksum([t0, t1]),
+        h0, h1, Crc::SENSIRION.checksum([h0, h1]),
+    ]
+}
+
+/// Parse a measurement frame at the start of `buf`, e.g. a DMA buffer,
+/// without copying it.
+///
+/// Like [`parse_frame`], but fails with [`Error::ShortRead`] if `buf` is
+/// shorter than 6 bytes. Anything after the first 6 bytes is ignored.
+pub fn parse_frame_from<E>(buf: &[u8]) -> Result<Measurement, Error<E>> {
+    let frame: &[u8; 6] = buf
+        .get(..6)
+        .and_then(|frame| frame.try_into().ok())
+        .ok_or(Error::ShortRead)?;
+    Ok(parse_frame(frame)?)
+}
+
+/// Parse a measurement frame whose data words arrived byte-swapped
+/// (little-endian).
+///
+/// The sensor always sends big-endian words, so this is only for debugging
+/// transports or bridges that swap the bytes of each word. The CRC bytes are
+/// checked against the words in their original order.
+pub fn parse_frame_le(frame: &[u8; 6]) -> Result<Measurement, CrcError> {
+    let [t1, t0, tc, h1, h0, hc] = *frame;
+    parse_frame(&[t0, t1, tc, h0, h1, hc])
+}
+
+/// Parse a raw 6-byte measurement frame using the given CRC parameters.
+fn parse_frame_with<C: Conversion>(frame: &[u8; 6], crc: &Crc) -> Result<Measurement, CrcError> {
+    let temperature = check_crc([frame[0], frame[1]], frame[2], crc)
+        .map(C::temperature)?;
+    let humidity = check_crc([frame[3], frame[4]], frame[5], crc)
+        .map(C::humidity)?;
+
+    Ok(Measurement{ temperature, humidity })
+}
+
+/// Convert centidegrees Celsius to the nearest raw temperature reading.
+///
+/// The inverse of [`convert_temperature`], e.g. for building expected frames
+/// or lookup tables in `const` context. Rounds to the nearest raw value,
+/// halves up, and clamps to the raw range. Since the forward conversion
+/// rounds down, converting the result back gives `centi` or one centidegree
+/// less.
+pub const fn temperature_to_raw(centi: i32) -> u16 {
+    let raw = ((centi as i64 + 4500) * 65535 + 17500 / 2).div_euclid(17500);
+    if raw < 0 {
+        0
+    } else if raw > u16::MAX as i64 {
+        u16::MAX
+    } else {
+        raw as u16
+    }
+}
+
+/// Convert centi-percent relative humidity to the nearest raw reading.
+///
+/// The inverse of [`convert_humidity`], rounding like [`temperature_to_raw`]:
+/// to the nearest raw value, halves up, clamped to the raw range, so
+/// converting back gives `centi` or one centi-percent less.
+pub const fn humidity_to_raw(centi: u16) -> u16 {
+    let raw = (centi as u32 * 65535 + 10000 / 2) / 10000;
+    if raw > u16::MAX as u32 {
+        u16::MAX
+    } else {
+        raw as u16
+    }
+}
+
+/// Convert a raw 6-byte measurement frame without checking the CRCs.
+fn parse_frame_unchecked<C: Conversion>(frame: &[u8; 6]) -> Measurement {
+    let temperature = C::temperature(u16::from_be_bytes([frame[0], frame[1]]));
+    let humidity = C::humidity(u16::from_be_bytes([frame[3], frame[4]]));
+
+    Measurement{ temperature, humidity }
+}
+
+/// Conversion from raw sensor readings to physical values
+///
+/// The driver uses [`SensirionConversion`] unless told otherwise. Clones and
+/// custom calibration schemes can supply their own curve:
+///
+/// ```
+/// use sht3x::Conversion;
+///
+/// #[derive(Debug, Clone)]
+/// struct Offset;
+///
+/// impl Conversion for Offset {
+///     fn temperature(raw: u16) -> i32 {
+///         sht3x::SensirionConversion::temperature(raw) - 150
+///     }
+///
+///     fn humidity(raw: u16) -> u16 {
+///         sht3x::SensirionConversion::humidity(raw)
+///     }
+/// }
+/// ```
+///
+/// and switch to it with [`Sht3x::with_conversion`]. Both functions are
+/// called with the raw 16-bit reading and return centidegrees Celsius and
+/// centi-percent respectively.
+pub trait Conversion {
+    /// Convert a raw temperature reading to centidegrees Celsius.
+    fn temperature(raw: u16) -> i32;
+    /// Convert a raw humidity reading to centi-percent.
+    fn humidity(raw: u16) -> u16;
+}
+
+/// The conversion formulas from the datasheet (4.13 Conversion of Signal
+/// Output)
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SensirionConversion;
+
+impl Conversion for SensirionConversion {
+    fn temperature(raw: u16) -> i32 {
+        convert_temperature(raw)
+    }
+
+    fn humidity(raw: u16) -> u16 {
+        convert_humidity(raw)
+    }
+}
+
+/// Linear conversion with a custom full-scale range, for compatible parts
+///
+/// Temperature is `T_OFFSET + T_SPAN * raw / 65535` centidegrees and
+/// humidity `RH_SPAN * raw / 65535` centi-percent. Genuine Sensirion parts
+/// use -4500, 17500 and 10000, which is what [`SensirionConversion`]
+/// implements; only use this for clones that document a different range:
+///
+/// ```
+/// type CloneConversion = sht3x::ScaledConversion<-4000, 16500, 10000>;
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ScaledConversion<const T_OFFSET: i32, const T_SPAN: i32, const RH_SPAN: u16>;
+
+impl<const T_OFFSET: i32, const T_SPAN: i32, const RH_SPAN: u16> Conversion for ScaledConversion<T_OFFSET, T_SPAN, RH_SPAN> {
+    fn temperature(raw: u16) -> i32 {
+        (T_OFFSET as i64 + (T_SPAN as i64 * raw as i64) / 65535) as i32
+    }
+
+    fn humidity(raw: u16) -> u16 {
+        ((RH_SPAN as u32 * raw as u32) / 65535) as u16
+    }
+}
+
+/// Table-based conversion for targets where division is expensive
+///
+/// Readings are interpolated between 17 precomputed points, which replaces
+/// the division in [`SensirionConversion`] with a multiply and a shift. The
+/// result is at most 1 centidegree / 1 centi-percent off the exact
+/// conversion. Enable it with
+/// [`with_conversion::<LutConversion>()`](Sht3x::with_conversion).
+#[cfg(feature = "lut-convert")]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct LutConversion;
+
+#[cfg(feature = "lut-convert")]
+impl LutConversion {
+    const SHIFT: u32 = 12;
+    const POINTS: usize = (1 << (16 - Self::SHIFT)) + 1;
+
+    const TEMPERATURE: [i32; Self::POINTS] = {
+        let mut table = [0; Self::POINTS];
+        let mut i = 0;
+        while i < Self::POINTS {
+            table[i] = (-4500 + (17500 * ((i as i64) << Self::SHIFT)) / 65535) as i32;
+            i += 1;
+        }
+        table
+    };
+
+    const HUMIDITY: [u32; Self::POINTS] = {
+        let mut table = [0; Self::POINTS];
+        let mut i = 0;
+        while i < Self::POINTS {
+            table[i] = ((10000 * ((i as u64) << Self::SHIFT)) / 65535) as u32;
+            i += 1;
+        }
+        table
+    };
+}
+
+#[cfg(feature = "lut-convert")]
+impl Conversion for LutConversion {
+    fn temperature(raw: u16) -> i32 {
+        let i = (raw >> Self::SHIFT) as usize;
+        let frac = (raw & ((1 << Self::SHIFT) - 1)) as i32;
+        let (start, end) = (Self::TEMPERATURE[i], Self::TEMPERATURE[i + 1]);
+        start + (((end - start) * frac) >> Self::SHIFT)
+    }
+
+    fn humidity(raw: u16) -> u16 {
+        let i = (raw >> Self::SHIFT) as usize;
+        let frac = (raw & ((1 << Self::SHIFT) - 1)) as u32;
+        let (start, end) = (Self::HUMIDITY[i], Self::HUMIDITY[i + 1]);
+        (start + (((end - start) * frac) >> Self::SHIFT)) as u16
+    }
+}
+
+/// Convert a raw temperature reading to centidegrees Celsius.
+///
+/// The conversion [`SensirionConversion`] uses, rounding down (towards
+/// −∞, so raw 1, −44.997 °C, gives −4500). Usable in `const` context, e.g.
+/// for build-time lookup tables.
+pub const fn convert_temperature(raw: u16) -> i32 {
+    -4500 + (17500 * raw as i32) / 65535
+}
+
+/// Convert a raw temperature reading to millidegrees Celsius.
+///
+/// Measurements are reported in centidegrees; this keeps one more digit of
+/// the sensor's resolution for callers working with raw readings.
+pub const fn convert_temperature_precise(raw: u16) -> i32 {
+    (-45000 + (175000 * raw as i64) / 65535) as i32
+}
+
+/// Convert a raw humidity reading to centi-percent relative humidity.
+///
+/// The conversion [`SensirionConversion`] uses, rounding down. Usable in
+/// `const` context like [`convert_temperature`].
+pub const fn convert_humidity(raw: u16) -> u16 {
+    ((10000 * raw as u32) / 65535) as u16
+}
+
+/// Wait for `ms` milliseconds with a delay that only takes `u8` values.
+fn delay_ms_long<D: DelayMs<u8>>(delay: &mut D, ms: u16) {
+    let mut remaining = ms;
+    while remaining > 0 {
+        let step = remaining.min(u8::MAX as u16);
+        delay.delay_ms(step as u8);
+        remaining -= step;
+    }
+}
+
+/// Check that every 3-byte word of a response was received, see
+/// `read_words`.
+fn check_short_read<E>(buf: &[u8], crc: &Crc) -> Result<(), Error<E>> {
+    if crc.checksum([0xFF, 0xFF]) != 0xFF && buf.chunks(3).any(|word| word == [0xFF; 3]) {
+        return Err(Error::ShortRead);
+    }
+    Ok(())
+}
+
+/// Compare the CRC of the input array to the given CRC checksum.
+fn check_crc(data: [u8; 2], crc: u8, params: &Crc) -> Result<u16, CrcError> {
+    let calculated_crc = params.checksum(data);
+
+    if calculated_crc == crc {
+        Ok(u16::from_be_bytes(data))
+    } else {
+        warn!("CRC mismatch: expected {:#04x}, received {:#04x}", calculated_crc, crc);
+        Err(CrcError)
+    }
+}
+
+/// Check the CRC of every 3-byte word (data plus CRC) in `data` in one
+/// pass.
+///
+/// Accepts and rejects exactly the same frames as calling [`check_crc`] on
+/// each word.
+fn check_words(data: &[u8], params: &Crc) -> Result<(), CrcError> {
+    for word in data.chunks_exact(3) {
+        let calculated_crc = params.checksum_slice(&word[..2]);
+        if calculated_crc != word[2] {
+            warn!("CRC mismatch: expected {:#04x}, received {:#04x}", calculated_crc, word[2]);
+            return Err(CrcError);
+        }
+    }
+    Ok(())
+}
+
+/// CRC8 parameters
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Crc {
+    /// Initial value
+    pub init: u8,
+    /// Polynomial
+    pub polynomial: u8,
+}
+
+impl Crc {
+    /// 4.12 Checksum Calculation
+    /// Table 19
+    pub const SENSIRION: Crc = Crc { init: 0xFF, polynomial: 0x31 };
+
+    /// Calculate the CRC8 checksum for the given input array.
+    pub const fn checksum(&self, data: [u8; 2]) -> u8 {
+        self.checksum_slice(&data)
+    }
+
+    /// Calculate the CRC8 checksum of any number of bytes.
+    pub const fn checksum_slice(&self, data: &[u8]) -> u8 {
+        let mut crc = self.init;
+
+        let mut i = 0;
+        while i < data.len() {
+            crc ^= data[i];
+
+            let mut bit = 0;
+            while bit < 8 {
+                if crc & 0x80 > 0 {
+                    crc = (crc << 1) ^ self.polynomial;
+                } else {
+                    crc <<= 1;
+                }
+                bit += 1;
+            }
+            i += 1;
+        }
+
+        crc
+    }
+}
+
+/// Errors
+#[derive(Debug)]
+pub enum Error<E> {
+    /// Wrong CRC
+    Crc,
+    /// I2C bus error
+    I2c(E),
+    /// Fewer bytes received than requested
+    ShortRead,
+    /// The sensor didn't report a reset after being reset
+    ResetNotDetected,
+    /// I2C address doesn't fit in 7 bits
+    InvalidAddress,
+    /// The sensor rejected the checksum of written data
+    WriteChecksum,
+    /// A measurement failed the [`Pipeline`] range check
+    OutOfRange,
+    /// No two consecutive readings agreed, see [`Sht3x::measure_stable`],
+    /// or the humidity didn't recover, see [`Sht3x::wait_for_rh_recovery`]
+    Unstable,
+    /// The readings before and after the settling time differed, see
+    /// [`Sht3x::measure_settled`]
+    NotSettled,
+    /// The sensor reported that a command failed, see
+    /// [`Sht3x::with_verify_commands`]
+    CommandRejected,
+    /// SDA is held low while the bus should be idle, see `check_bus`
+    BusStuck,
+    /// GPIO error while checking or recovering the bus, see `check_bus` and
+    /// `recover_bus`
+    Pin(E),
+    /// The sensor reset since the status was last cleared, see
+    /// [`Sht3x::measure_checked_status`]
+    UnexpectedReset,
+    /// A reading changed faster than physically possible, see [`RateLimit`]
+    ImplausibleJump,
+    /// No data before the caller's timeout, see [`Sht3x::measure_polled`]
+    Timeout,
+}
+
+impl<E> Error<E> {
+    /// The underlying I2C error, if this is a bus error.
+    ///
+    /// This makes it easy to bridge into an application error type:
+    ///
+    /// ```
+    /// enum AppError<E> {
+    ///     Bus(E),
+    ///     Sensor,
+    /// }
+    ///
+    /// impl<E> From<sht3x::Error<E>> for AppError<E> {
+    ///     fn from(err: sht3x::Error<E>) -> Self {
+    ///         match err.into_inner() {
+    ///             Some(e) => AppError::Bus(e),
+    ///             None => AppError::Sensor,
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn into_inner(self) -> Option<E> {
+        match self {
+            Error::I2c(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Wrong CRC, for operations that don't touch the bus
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct CrcError;
+
+impl<E> From<CrcError> for Error<E> {
+    fn from(_: CrcError) -> Self {
+        Error::Crc
+    }
+}
+
+/// I2C address
+#[derive(Debug, Copy, Clone)]
+pub enum Address {
+    /// Address pin held high
+    High = 0x45,
+    /// Address pin held low
+    Low = 0x44,
+}
+
+impl Address {
+    /// The other address: `High` for `Low` and `Low` for `High`.
+    ///
+    /// Handy for talking to both sensors on a bus, e.g.
+    /// `for addr in [Address::Low, Address::Low.other()]`.
+    pub const fn other(&self) -> Address {
+        match *self {
+            Address::High => Address::Low,
+            Address::Low => Address::High,
+        }
+    }
+}
+
+/// SHT3x part variant
+///
+/// Sensirion doesn't publish a mapping from serial numbers to variants, so
+/// the driver can't detect the variant itself; applications that need it
+/// have to know which part is fitted.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SensorVariant {
+    Sht30,
+    Sht31,
+    Sht35,
+    /// Not known; accuracy figures are those of the least accurate part
+    Unknown,
+}
+
+impl SensorVariant {
+    /// Typical relative humidity accuracy in centi-percent
+    ///
+    /// 1.1 Relative Humidity, Table 1: ±2 / ±2 / ±1.5 %RH for the
+    /// SHT30 / SHT31 / SHT35. Accuracy is the same for every
+    /// [`Repeatability`], which only affects noise. It degrades towards the
+    /// ends of the humidity range (see Figures 1–3 of the datasheet).
+    pub const fn accuracy_rh_centi(&self) -> u16 {
+        match *self {
+            SensorVariant::Sht30 | SensorVariant::Sht31 | SensorVariant::Unknown => 200,
+            SensorVariant::Sht35 => 150,
+        }
+    }
+
+    /// Typical temperature accuracy in centidegrees Celsius
+    ///
+    /// 1.2 Temperature, Table 2: ±0.2 / ±0.2 / ±0.1 °C for the
+    /// SHT30 / SHT31 / SHT35. Like the humidity accuracy it's independent of
+    /// the repeatability and degrades towards the ends of the temperature
+    /// range (see Figures 4–6 of the datasheet).
+    pub const fn accuracy_temp_centi(&self) -> u16 {
+        match *self {
+            SensorVariant::Sht30 | SensorVariant::Sht31 | SensorVariant::Unknown => 20,
+            SensorVariant::Sht35 => 10,
+        }
+    }
+}
+
+/// Clock stretching
+///
+/// This only selects the command sent to the sensor; it doesn't change how
+/// the driver decides that a conversion is done, which is fixed per method:
+///
+/// - [`Sht3x::measure`] and the methods built on it always wait the full
+///   conversion time before reading, for both variants. The reading is
+///   fresh as long as the delay is at least as long as requested.
+/// - [`Sht3x::measure_clock_stretch`] relies on the sensor holding SCL low
+///   until the result is ready, so completion is confirmed by the hardware.
+/// - [`Sht3x::measure_polled`] retries the read until the sensor stops
+///   NACKing it, which the sensor also only does once the result is ready.
+///
+/// There is no automatic fallback between these, so the method a reading
+/// came from says how its timing was validated.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ClockStretch {
+    Enabled,
+    Disabled,
+}
+
+/// Data acquisition mode
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Mode {
+    /// Single shot data acquisition
+    SingleShot,
+    /// Periodic data acquisition
+    Periodic(Rate, Repeatability),
+    /// Periodic data acquisition with accelerated response time (ART), at
+    /// 4 measurements per second
+    Art,
+}
+
+/// Periodic data acquisition rate
+///
+/// Every rate works with every [`Repeatability`] (4.5 Periodic Data
+/// Acquisition Mode, Table 9): even at 10 measurements per second, a high
+/// repeatability conversion takes at most 15 of the 100 ms. At that rate
+/// the sensor may heat itself slightly, though, which biases the
+/// temperature up and the humidity down.
+#[allow(non_camel_case_types)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Rate {
+    /// 0.5 measurements per second
+    R0_5,
+    /// 1 measurement per second
+    R1,
+    /// 2 measurements per second
+    R2,
+    /// 4 measurements per second
+    R4,
+    /// 10 measurements per second
+    R10,
+}
+
+impl Rate {
+    /// Time between two measurements in milliseconds
+    pub const fn interval_ms(&self) -> u16 {
+        match *self {
+            Rate::R0_5 => 2000,
+            Rate::R1 => 1000,
+            Rate::R2 => 500,
+            Rate::R4 => 250,
+            Rate::R10 => 100,
+        }
+    }
+
+    /// Bus data bytes per second when fetching every measurement.
+    ///
+    /// Counts the 2-byte fetch command and the 6-byte response per
+    /// measurement, but no address bytes, ACKs or start/stop conditions.
+    pub const fn bus_bytes_per_second(&self) -> u16 {
+        8 * 1000 / self.interval_ms()
+    }
+}
+
+/// Measurement repeatability
+///
+/// The sensor measures temperature and humidity with the same repeatability;
+/// see [`Sht3x::measure_split`] to get different ones for each.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Repeatability {
+    High,
+    Medium,
+    Low,
+}
+
+impl Repeatability {
+    /// Maximum measurement duration in milliseconds
+    const fn max_duration(&self) -> u8 {
+        match *self {
+            Repeatability::Low => 4,
+            Repeatability::Medium => 6,
+            Repeatability::High => 15,
+        }
+    }
+
+    /// Shortest time between starting two single shot measurements, in
+    /// milliseconds; this is the maximum conversion time.
+    ///
+    /// For a cadence the periodic [`Rate`]s don't offer, time single shot
+    /// measurements yourself, with a period of at least this long:
+    /// [`Sht3x::measure`] already waits this long for the conversion, so
+    /// wait `period - min_period_ms()` between calls. The sensor doesn't
+    /// answer while it's still converting, so starting measurements more
+    /// often gives errors rather than fresh readings.
+    pub const fn min_period_ms(&self) -> u8 {
+        self.max_duration()
+    }
+
+    /// Typical energy of one single shot measurement in nanojoules.
+    ///
+    /// 2.1 Electrical Specifications, Table 4 and 2.2 Timing Specification,
+    /// Table 5: 600 µA while measuring at 3.3 V, for 12.5 / 4.5 / 2.5 ms for
+    /// high / medium / low repeatability.
+    const fn energy_nj(&self) -> u32 {
+        let duration_us = match *self {
+            Repeatability::Low => 2500,
+            Repeatability::Medium => 4500,
+            Repeatability::High => 12500,
+        };
+        // 600 µA at 3.3 V is 1.98 nJ per µs.
+        duration_us * 198 / 100
+    }
+
+    /// Typical energy of one single shot measurement in microjoules,
+    /// rounded to the nearest microjoule: 25 / 9 / 5 µJ for high / medium /
+    /// low repeatability.
+    ///
+    /// This assumes the typical 600 µA measurement current at 3.3 V; the real
+    /// energy scales with the supply voltage and varies between parts. Bus
+    /// traffic and idle current aren't included.
+    pub const fn energy_uj(&self) -> u32 {
+        (self.energy_nj() + 500) / 1000
+    }
+
+    /// Typical relative humidity repeatability (noise) in centi-percent
+    ///
+    /// 1.1 Relative Humidity, Table 1: 0.08 / 0.15 / 0.21 %RH for
+    /// high / medium / low repeatability.
+    pub const fn noise_rh_centi(&self) -> u16 {
+        match *self {
+            Repeatability::Low => 21,
+            Repeatability::Medium => 15,
+            Repeatability::High => 8,
+        }
+    }
+
+    /// Typical temperature repeatability (noise) in centidegrees Celsius
+    ///
+    /// 1.2 Temperature, Table 2: 0.04 / 0.08 / 0.15 °C for
+    /// high / medium / low repeatability.
+    pub const fn noise_temp_centi(&self) -> u16 {
+        match *self {
+            Repeatability::Low => 15,
+            Repeatability::Medium => 8,
+            Repeatability::High => 4,
+        }
+    }
+
+    /// Typical relative humidity accuracy in centi-percent, ±2 %RH at every
+    /// level
+    ///
+    /// 1.1 Relative Humidity, Table 1. Unlike the
+    /// [noise](Self::noise_rh_centi), the accuracy doesn't depend on the
+    /// repeatability. This is the SHT30/SHT31 figure; the SHT35 is more
+    /// accurate, see [`SensorVariant::accuracy_rh_centi`]. It degrades towards
+    /// the ends of the humidity range (see Figures 1–3 of the datasheet).
+    pub const fn accuracy_rh_centi(&self) -> u16 {
+        SensorVariant::Sht31.accuracy_rh_centi()
+    }
+
+    /// Typical temperature accuracy in centidegrees Celsius, ±0.2 °C at
+    /// every level
+    ///
+    /// 1.2 Temperature, Table 2, for the SHT30/SHT31 like
+    /// [`accuracy_rh_centi`](Self::accuracy_rh_centi); see
+    /// [`SensorVariant::accuracy_temp_centi`] for the SHT35. It degrades
+    /// towards the ends of the temperature range (see Figures 4–6).
+    pub const fn accuracy_temp_centi(&self) -> u16 {
+        SensorVariant::Sht31.accuracy_temp_centi()
+    }
+}
+
+/// Alert threshold register
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum AlertThreshold {
+    /// Alert is raised above this limit
+    HighSet,
+    /// Alert is cleared below this limit
+    HighClear,
+    /// Alert is cleared above this limit
+    LowClear,
+    /// Alert is raised below this limit
+    LowSet,
+}
+
+/// A temperature and humidity alert threshold
+///
+/// The sensor only stores the 9 most significant bits of the raw temperature
+/// and the 7 most significant bits of the raw humidity, so a limit read back
+/// from the sensor is the nearest representable value, not necessarily the
+/// one that was written.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AlertLimit {
+    /// Temperature in centidegrees Celsius
+    pub temperature: i32,
+    /// Relative humidity in centi-percent
+    pub humidity: u16,
+}
+
+impl AlertLimit {
+    /// Decode a limit from the sensor's packed representation.
+    pub const fn from_raw(word: u16) -> Self {
+        Self {
+            temperature: convert_temperature((word & 0x01FF) << 7),
+            humidity: convert_humidity(word & 0xFE00),
+        }
+    }
+
+    /// Encode the limit into the sensor's packed representation, rounding
+    /// each value to the nearest representable one.
+    pub const fn to_raw(&self) -> u16 {
+        let mut temperature = (temperature_to_raw(self.temperature) as u32 + 0x40) >> 7;
+        if temperature > 0x01FF {
+            temperature = 0x01FF;
+        }
+        let mut humidity = (humidity_to_raw(self.humidity) as u32 + 0x100) >> 9;
+        if humidity > 0x7F {
+            humidity = 0x7F;
+        }
+        ((humidity << 9) | temperature) as u16
+    }
+}
+
+/// The four alert thresholds
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AlertLimits {
+    pub high_set: AlertLimit,
+    pub high_clear: AlertLimit,
+    pub low_clear: AlertLimit,
+    pub low_set: AlertLimit,
+}
+
+impl AlertLimits {
+    /// Limits that never raise an alert: both high limits are at the top of
+    /// the range (raw `0xFFFF`) and both low limits at the bottom (raw
+    /// `0x0000`).
+    pub const DISABLED: AlertLimits = AlertLimits {
+        high_set: AlertLimit::from_raw(0xFFFF),
+        high_clear: AlertLimit::from_raw(0xFFFF),
+        low_clear: AlertLimit::from_raw(0x0000),
+        low_set: AlertLimit::from_raw(0x0000),
+    };
+
+    /// The humidity in percent the sensor would actually store for a limit
+    /// of `percent`.
+    ///
+    /// Lets configuration tools show the effective threshold before writing
+    /// it, see [`AlertLimit`] for the quantization.
+    #[cfg(feature = "float")]
+    pub fn quantize_rh(percent: f32) -> f32 {
+        Self::quantize(0.0, percent).humidity_percent()
+    }
+
+    /// The temperature in degrees Celsius the sensor would actually store
+    /// for a limit of `celsius`, see [`quantize_rh`](Self::quantize_rh).
+    #[cfg(feature = "float")]
+    pub fn quantize_temp(celsius: f32) -> f32 {
+        Self::quantize(celsius, 0.0).temperature_celsius()
+    }
+
+    #[cfg(feature = "float")]
+    fn quantize(celsius: f32, percent: f32) -> Measurement {
+        let m = Measurement::from_celsius_percent(celsius, percent);
+        let limit = AlertLimit::from_raw(AlertLimit { temperature: m.temperature, humidity: m.humidity }.to_raw());
+        Measurement { temperature: limit.temperature, humidity: limit.humidity }
+    }
+
+    fn get_mut(&mut self, threshold: AlertThreshold) -> &mut AlertLimit {
+        match threshold {
+            AlertThreshold::HighSet => &mut self.high_set,
+            AlertThreshold::HighClear => &mut self.high_clear,
+            AlertThreshold::LowClear => &mut self.low_clear,
+            AlertThreshold::LowSet => &mut self.low_set,
+        }
+    }
+}
+
+/// Formats the limit like a [`Measurement`], e.g. `35.00 °C, 95.00 %RH`.
+impl fmt::Display for AlertLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Measurement { temperature: self.temperature, humidity: self.humidity }.fmt(f)
+    }
+}
+
+/// Formats all four thresholds in physical units, for consoles and logs.
+///
+/// For limits from [`Sht3x::read_alert_limits`] these are the quantized
+/// values the sensor actually stored, which can differ slightly from the
+/// ones that were written.
+impl fmt::Display for AlertLimits {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "high set {}; high clear {}; low clear {}; low set {}",
+            self.high_set, self.high_clear, self.low_clear, self.low_set,
+        )
+    }
+}
+
+/// Alert thresholds for common applications
+///
+/// Each preset lists its thresholds as high set / high clear / low clear /
+/// low set. Use [`AlertLimits`] directly for anything else.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum AlertPreset {
+    /// 28 / 26 / 18 / 16 °C and 70 / 65 / 25 / 20 %RH
+    IndoorComfort,
+    /// 8 / 6 / 2 / 0 °C and 95 / 90 / 0 / 0 %RH (no low humidity alert)
+    ColdStorage,
+    /// 35 / 32 / 12 / 10 °C and 95 / 90 / 45 / 40 %RH
+    Greenhouse,
+}
+
+impl AlertPreset {
+    /// The thresholds of this preset.
+    pub const fn limits(&self) -> AlertLimits {
+        const fn limit(temperature: i32, humidity: u16) -> AlertLimit {
+            AlertLimit { temperature, humidity }
+        }
+
+        match *self {
+            AlertPreset::IndoorComfort => AlertLimits {
+                high_set: limit(2800, 7000),
+                high_clear: limit(2600, 6500),
+                low_clear: limit(1800, 2500),
+                low_set: limit(1600, 2000),
+            },
+            AlertPreset::ColdStorage => AlertLimits {
+                high_set: limit(800, 9500),
+                high_clear: limit(600, 9000),
+                low_clear: limit(200, 0),
+                low_set: limit(0, 0),
+            },
+            AlertPreset::Greenhouse => AlertLimits {
+                high_set: limit(3500, 9500),
+                high_clear: limit(3200, 9000),
+                low_clear: limit(1200, 4500),
+                low_set: limit(1000, 4000),
+            },
+        }
+    }
+}
+
+/// Sensor commands
+///
+/// Exposed so that tooling such as bus replayers and mock tests can build
+/// the exact bytes the driver sends, see [`Command::value`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Command {
+    /// Single shot measurement
+    SingleShot(ClockStretch, Repeatability),
+    /// Start periodic measurements
+    Periodic(Rate, Repeatability),
+    /// Fetch periodic measurement data
+    FetchData,
+    /// Start periodic measurements with accelerated response time
+    PeriodicWithART,
+    /// Stop periodic measurements
+    Break,
+    /// Soft reset
+    SoftReset,
+    /// Turn the heater on
+    HeaterEnable,
+    /// Turn the heater off
+    HeaterDisable,
+    /// Read the status register
+    Status,
+    /// Clear the status register
+    ClearStatus,
+    /// Read the serial number
+    SerialNumber,
+    /// Read an alert threshold
+    ReadAlertLimit(AlertThreshold),
+    /// Write an alert threshold
+    WriteAlertLimit(AlertThreshold),
+}
+
+impl Command {
+    /// The 16-bit command code, sent MSB first.
+    ///
+    /// Values are from the command tables in section 4 of the datasheet.
+    pub const fn value(&self) -> u16 {
+        use ClockStretch::Enabled as CSEnabled;
+        use ClockStretch::Disabled as CSDisabled;
+        use Rate::*;
+        use Repeatability::*;
+        match *self {
+            // 4.3 Measurement Commands for Single Shot Data Acquisition Mode
+            // Table 8
+            Command::SingleShot(CSEnabled,  High)   => 0x2C06,
+            Command::SingleShot(CSEnabled,  Medium) => 0x2C0D,
+            Command::SingleShot(CSEnabled,  Low)    => 0x2C10,
+            Command::SingleShot(CSDisabled, High)   => 0x2400,
+            Command::SingleShot(CSDisabled, Medium) => 0x240B,
+            Command::SingleShot(CSDisabled, Low)    => 0x2416,
+
+            // 4.5 Measurement Commands for Periodic Data Acquisition Mode
+            // Table 9
+            Command::Periodic(R0_5, High)   => 0x2032,
+            Command::Periodic(R0_5, Medium) => 0x2024,
+            Command::Periodic(R0_5, Low)    => 0x202F,
+            Command::Periodic(R1,   High)   => 0x2130,
+            Command::Periodic(R1,   Medium) => 0x2126,
+            Command::Periodic(R1,   Low)    => 0x212D,
+            Command::Periodic(R2,   High)   => 0x2236,
+            Command::Periodic(R2,   Medium) => 0x2220,
+            Command::Periodic(R2,   Low)    => 0x222B,
+            Command::Periodic(R4,   High)   => 0x2334,
+            Command::Periodic(R4,   Medium) => 0x2322,
+            Command::Periodic(R4,   Low)    => 0x2329,
+            Command::Periodic(R10,  High)   => 0x2737,
+            Command::Periodic(R10,  Medium) => 0x2721,
+            Command::Periodic(R10,  Low)    => 0x272A,
+
+            // 4.6 Readout of Measurement Results for Periodic Mode
+            // Table 10
+            Command::FetchData => 0xE000,
+
+            // 4.7 ART command
+            // Table 11
+            Command::PeriodicWithART => 0x2B32,
+
+            // 4.8 Break command
+            // Table 12
+            Command::Break => 0x3093,
+
+            // 4.9 Reset
+            // Table 13
+            Command::SoftReset => 0x30A2,
+
+            // 4.10 Heater
+            // Table 15
+            Command::HeaterEnable  => 0x306D,
+            Command::HeaterDisable => 0x3066,
+
+            // 4.11 Status register
+            // Table 16
+            Command::Status => 0xF32D,
+            // Table 18
+            Command::ClearStatus => 0x3041,
+
+            // Application note: Electronic Identification Code
+            // Read serial number, clock stretching disabled
+            Command::SerialNumber => 0x3780,
+
+            // Application note: Alert Mode
+            Command::ReadAlertLimit(AlertThreshold::HighSet)   => 0xE11F,
+            Command::ReadAlertLimit(AlertThreshold::HighClear) => 0xE114,
+            Command::ReadAlertLimit(AlertThreshold::LowClear)  => 0xE109,
+            Command::ReadAlertLimit(AlertThreshold::LowSet)    => 0xE102,
+            Command::WriteAlertLimit(AlertThreshold::HighSet)   => 0x611D,
+            Command::WriteAlertLimit(AlertThreshold::HighClear) => 0x6116,
+            Command::WriteAlertLimit(AlertThreshold::LowClear)  => 0x610B,
+            Command::WriteAlertLimit(AlertThreshold::LowSet)    => 0x6100,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Measurement {
+    pub temperature: i32,
+    pub humidity: u16,
+}
+
+impl Measurement {
+    /// Temperature in degrees Celsius.
+    ///
+    /// With 16 fractional bits, the resolution (~0.000015 °C) is finer than
+    /// the centidegree reading it's derived from.
+    #[cfg(feature = "fixed")]
+    pub fn temperature_fixed(&self) -> fixed::types::I16F16 {
+        fixed::types::I16F16::from_num(self.temperature) / 100
+    }
+
+    /// Relative humidity in percent.
+    ///
+    /// With 16 fractional bits, the resolution (~0.000015 %RH) is finer than
+    /// the centi-percent reading it's derived from.
+    #[cfg(feature = "fixed")]
+    pub fn humidity_fixed(&self) -> fixed::types::U16F16 {
+        fixed::types::U16F16::from_num(self.humidity) / 100
+    }
+
+    /// Temperature in decidegrees Celsius, for systems that use tenths of a
+    /// degree.
+    ///
+    /// Rounded to the nearest decidegree, halves away from zero, which drops
+    /// the reading's last digit.
+    pub const fn temperature_decidegrees(&self) -> i32 {
+        let half = if self.temperature < 0 { -5 } else { 5 };
+        self.temperature.saturating_add(half) / 10
+    }
+
+    /// Temperature in tenths of a degree and humidity in whole percent,
+    /// rounded for display, e.g. `(234, 51)` for 23.4 °C and 51 %RH.
+    ///
+    /// The same rounding as [`temperature_decidegrees`](Self::temperature_decidegrees)
+    /// (halves away from zero, so -12.35 °C is -124) and
+    /// [`humidity_percent_u8`](Self::humidity_percent_u8). Temperatures
+    /// outside the `i16` range, which the sensor can't report, saturate.
+    pub const fn rounded_display(&self) -> (i16, i16) {
+        let decidegrees = self.temperature_decidegrees();
+        let temperature = if decidegrees > i16::MAX as i32 {
+            i16::MAX
+        } else if decidegrees < i16::MIN as i32 {
+            i16::MIN
+        } else {
+            decidegrees as i16
+        };
+        (temperature, self.humidity_percent_u8() as i16)
+    }
+
+    /// Format the reading for a small display, e.g. `" 23.4C  51%"`.
+    ///
+    /// For anything the sensor can report (-45 °C to 130 °C) the result is
+    /// 11 characters, so `N` has to be at least 11:
+    /// the temperature in tenths of a degree right-aligned to 5 characters
+    /// (see [`temperature_decidegrees`](Self::temperature_decidegrees) for
+    /// the rounding), `C`, a space, and the humidity in whole percent
+    /// right-aligned to 3 characters (see
+    /// [`humidity_percent_u8`](Self::humidity_percent_u8)), followed by `%`.
+    #[cfg(feature = "display-string")]
+    pub fn to_display_string<const N: usize>(&self) -> heapless::String<N> {
+        use fmt::Write;
+        const { assert!(N >= 11, "display strings are 11 characters long") };
+
+        let decidegrees = self.temperature_decidegrees();
+        let sign = if decidegrees < 0 { "-" } else { "" };
+        let abs = decidegrees.unsigned_abs();
+        // Formatted separately so the sign is padded along with the digits.
+        let mut temperature = heapless::String::<6>::new();
+        let mut out = heapless::String::new();
+        // Within the sensor's output range neither write runs out of space.
+        let _ = write!(temperature, "{}{}.{}", sign, abs / 10, abs % 10);
+        let _ = write!(out, "{:>5}C {:>3}%", temperature.as_str(), self.humidity_percent_u8());
+        out
+    }
+
+    /// Risk of mold growth under the current conditions, from 0 (none) to
+    /// 100 (high).
+    ///
+    /// A simplified isopleth heuristic after the VTT mold model: mold
+    /// starts growing above a critical humidity of 80 %RH from 20 °C up,
+    /// rising linearly to 95 %RH at 0 °C. The score is 0 up to 10 %RH below
+    /// the critical humidity and rises linearly to 100 at 15 %RH above it.
+    /// Below 0 °C and above 50 °C it's 0, as mold doesn't grow there.
+    ///
+    /// This only looks at one reading. Mold needs such conditions for days
+    /// or weeks, so act on sustained high scores, not single ones.
+    pub const fn mold_risk_score(&self) -> u8 {
+        if self.temperature < 0 || self.temperature > 5000 {
+            return 0;
+        }
+        let critical = if self.temperature >= 2000 { 8000 } else { 9500 - self.temperature * 3 / 4 };
+        let above = self.humidity as i32 - (critical - 1000);
+        if above <= 0 {
+            0
+        } else if above >= 2500 {
+            100
+        } else {
+            (above * 100 / 2500) as u8
+        }
+    }
+
+    /// Whether the reading is outside the band where `variant` meets its
+    /// typical accuracy ([`SensorVariant::accuracy_temp_centi`] and
+    /// [`SensorVariant::accuracy_rh_centi`]).
+    ///
+    /// The bands from Figures 1–6 of the datasheet are:
+    ///
+    /// | Variant | Temperature  | Humidity    |
+    /// |---------|--------------|-------------|
+    /// | SHT30   | 0 to 65 °C   | 10 to 90 %RH |
+    /// | SHT31   | 0 to 90 °C   | 0 to 100 %RH |
+    /// | SHT35   | 20 to 60 °C  | 0 to 80 %RH  |
+    ///
+    /// For [`SensorVariant::Unknown`] the band is where all three overlap,
+    /// 20 to 60 °C and 10 to 80 %RH. Outside them the reading is still
+    /// valid, just less accurate.
+    pub const fn accuracy_warning(&self, variant: SensorVariant) -> bool {
+        let (t_min, t_max, rh_min, rh_max) = match variant {
+            SensorVariant::Sht30 => (0, 6500, 1000, 9000),
+            SensorVariant::Sht31 => (0, 9000, 0, 10000),
+            SensorVariant::Sht35 => (2000, 6000, 0, 8000),
+            SensorVariant::Unknown => (2000, 6000, 1000, 8000),
+        };
+        self.temperature < t_min || self.temperature > t_max || self.humidity < rh_min || self.humidity > rh_max
+    }
+
+    /// The temperature and the humidity, each tagged with its unit.
+    pub const fn quantities(&self) -> (Quantity, Quantity) {
+        (
+            Quantity { value: self.temperature, unit: Unit::Centidegree },
+            Quantity { value: self.humidity as i32, unit: Unit::Centipercent },
+        )
+    }
+
+    /// Relative humidity in the integer representation `format`.
+    pub const fn humidity_as(&self, format: HumidityFormat) -> i32 {
+        match format {
+            HumidityFormat::CentiPercent => self.humidity as i32,
+            HumidityFormat::Percent => self.humidity_percent_u8() as i32,
+            HumidityFormat::FractionQ16 => ((self.humidity as u32 * 65536 + 5000) / 10000) as i32,
+        }
+    }
+
+    /// Relative humidity in whole percent, for compact telemetry.
+    ///
+    /// Rounded to the nearest percent (halves up) and clamped to 0–100, which
+    /// drops both decimal places of the reading.
+    pub const fn humidity_percent_u8(&self) -> u8 {
+        let percent = (self.humidity as u32 + 50) / 100;
+        if percent > 100 {
+            100
+        } else {
+            percent as u8
+        }
+    }
+
+    /// Pack into a compact 4-byte record: temperature as a big-endian `i16`
+    /// followed by humidity as a big-endian `u16`.
+    ///
+    /// The operating range (-40 °C to 125 °C) fits an `i16` of centidegrees
+    /// with plenty of margin; temperatures beyond ±327.67 °C are saturated.
+    pub const fn to_bytes(&self) -> [u8; 4] {
+        let temperature = if self.temperature > i16::MAX as i32 {
+            i16::MAX
+        } else if self.temperature < i16::MIN as i32 {
+            i16::MIN
+        } else {
+            self.temperature as i16
+        };
+        let [t0, t1] = temperature.to_be_bytes();
+        let [h0, h1] = self.humidity.to_be_bytes();
+        [t0, t1, h0, h1]
+    }
+
+    /// Unpack a record written by [`to_bytes`](Self::to_bytes).
+    pub const fn from_bytes(bytes: &[u8; 4]) -> Measurement {
+        Measurement {
+            temperature: i16::from_be_bytes([bytes[0], bytes[1]]) as i32,
+            humidity: u16::from_be_bytes([bytes[2], bytes[3]]),
+        }
+    }
+
+    /// Apply a fixed relative humidity offset, e.g. for a known enclosure
+    /// bias.
+    ///
+    /// The sensor's output is already temperature compensated; this is only a
+    /// single-offset trim in centi-percent. The result is clamped to
+    /// 0–100 %RH.
+    pub fn with_rh_correction(&self, offset_centi: i16) -> Measurement {
+        let humidity = (self.humidity as i32 + offset_centi as i32).clamp(RH_MIN_CENTI as i32, RH_MAX_CENTI as i32);
+        Measurement { temperature: self.temperature, humidity: humidity as u16 }
+    }
+
+    /// Index of the temperature bin in a histogram of `bins` bins, the first
+    /// starting at `min_centi` and each `bin_width_centi` wide.
+    ///
+    /// Readings below the first bin count towards the first one, readings at
+    /// or above the end of the last bin towards the last one. Panics if
+    /// `bin_width_centi` is zero.
+    pub fn temp_bin(&self, min_centi: i32, bin_width_centi: i32, bins: usize) -> usize {
+        bin_index(self.temperature as i64 - min_centi as i64, bin_width_centi as i64, bins)
+    }
+
+    /// Index of the humidity bin, see [`temp_bin`](Self::temp_bin).
+    pub fn humidity_bin(&self, min_centi: u16, bin_width_centi: u16, bins: usize) -> usize {
+        bin_index(self.humidity as i64 - min_centi as i64, bin_width_centi as i64, bins)
+    }
+
+    /// Average of two readings, e.g. from redundant sensors in the same
+    /// airflow. Both values are rounded down.
+    pub fn merge_mean(a: &Measurement, b: &Measurement) -> Measurement {
+        Measurement {
+            temperature: ((a.temperature as i64 + b.temperature as i64).div_euclid(2)) as i32,
+            humidity: ((a.humidity as u32 + b.humidity as u32) / 2) as u16,
+        }
+    }
+
+    /// Absolute temperature and humidity differences between two readings.
+    ///
+    /// A supervisor can compare these against the sensors' accuracy (see
+    /// [`SensorVariant`]) to flag a faulty sensor.
+    pub fn max_disagreement(a: &Measurement, b: &Measurement) -> (i32, u16) {
+        let temperature = a.temperature.abs_diff(b.temperature).min(i32::MAX as u32) as i32;
+        (temperature, a.humidity.abs_diff(b.humidity))
+    }
+
+    /// Whether both values are within the given tolerances of `other`
+    /// (inclusive), in centidegrees and centi-percent.
+    pub fn approx_eq(&self, other: &Measurement, temp_tol_centi: i32, rh_tol_centi: u16) -> bool {
+        let (dt, drh) = Measurement::max_disagreement(self, other);
+        dt <= temp_tol_centi && drh <= rh_tol_centi
+    }
+
+    /// Whether the air is saturated, i.e. fog or condensation is forming now.
+    ///
+    /// True from 99.5 %RH: at saturation the dew point equals the
+    /// temperature, and above 99.5 %RH it's within 0.2 °C of it over
+    /// the whole operating range, less than the sensor's accuracy.
+    pub const fn is_saturated(&self) -> bool {
+        self.humidity >= 9950
+    }
+
+    /// Whether the reading is physically possible.
+    ///
+    /// Both values have to be within the sensor's operating range. Above
+    /// 100 °C the saturation vapor pressure exceeds sea-level air pressure,
+    /// so the humidity also has to be below what is possible at 1013.25 hPa
+    /// (e.g. about 51 %RH at 120 °C). Readings taken at lower pressure are
+    /// bounded even more tightly, so this never rejects a real reading below
+    /// sea-level pressure, but it can't catch every implausible one.
+    pub fn is_plausible(&self) -> bool {
+        // Maximum relative humidity at 1013.25 hPa from 100 °C to 125 °C in
+        // 5 °C steps, in centi-percent.
+        const RH_MAX_ABOVE_BOILING: [u32; 6] = [10000, 8394, 7082, 6006, 5119, 4384];
+        const STEP: u32 = 500;
+
+        if !(TEMP_MIN_CENTI..=TEMP_MAX_CENTI).contains(&self.temperature)
+            || !(RH_MIN_CENTI..=RH_MAX_CENTI).contains(&self.humidity)
+        {
+            return false;
+        }
+        if self.temperature <= 10000 {
+            return true;
+        }
+
+        let offset = (self.temperature - 10000) as u32;
+        let i = (offset / STEP) as usize;
+        let max = match RH_MAX_ABOVE_BOILING.get(i + 1) {
+            Some(&next) => {
+                let start = RH_MAX_ABOVE_BOILING[i];
+                start - (start - next) * (offset % STEP) / STEP
+            }
+            None => RH_MAX_ABOVE_BOILING[i],
+        };
+        self.humidity as u32 <= max
+    }
+
+    /// Build a measurement from degrees Celsius and percent relative
+    /// humidity, e.g. for tests and simulators.
+    ///
+    /// Both values are rounded to the nearest centi-unit, so converting back
+    /// with [`temperature_celsius`](Self::temperature_celsius) and
+    /// [`humidity_percent`](Self::humidity_percent) is accurate to 0.005.
+    /// Humidity is clamped to 0–100 %RH and NaN maps to 0.
+    #[cfg(feature = "float")]
+    pub fn from_celsius_percent(temp_c: f32, rh: f32) -> Measurement {
+        let humidity = libm::roundf(rh * 100.0).clamp(RH_MIN_CENTI as f32, RH_MAX_CENTI as f32);
+        Measurement {
+            temperature: libm::roundf(temp_c * 100.0) as i32,
+            humidity: humidity as u16,
+        }
+    }
+
+    /// Temperature in degrees Celsius.
+    #[cfg(feature = "float")]
+    pub fn temperature_celsius(&self) -> f32 {
+        self.temperature as f32 / 100.0
+    }
+
+    /// Relative humidity in percent.
+    #[cfg(feature = "float")]
+    pub fn humidity_percent(&self) -> f32 {
+        self.humidity as f32 / 100.0
+    }
+
+    /// Temperature in degrees Celsius and relative humidity as a fraction
+    /// from 0.0 to 1.0, the inputs most meteorological formulas expect.
+    ///
+    /// Note the humidity is **not** in percent, unlike
+    /// [`humidity_percent`](Self::humidity_percent).
+    #[cfg(feature = "float")]
+    pub fn as_meteorology_inputs(&self) -> (f32, f32) {
+        (self.temperature_celsius(), self.humidity as f32 / 10000.0)
+    }
+
+    /// Measurement noise variance of this reading at `rpt` repeatability,
+    /// in °C² and (%RH)², e.g. for a Kalman filter's measurement covariance.
+    ///
+    /// Based on the repeatability noise ([`Repeatability::noise_temp_centi`]
+    /// and [`Repeatability::noise_rh_centi`]) as a standard deviation. Outside
+    /// the band where the sensor is most accurate (0–90 °C, 10–90 %RH) the
+    /// standard deviation is scaled up linearly, to 3 times at -40 °C and
+    /// 1.75 times at 0 and 100 %RH, roughly following how the accuracy
+    /// degrades in the datasheet.
+    #[cfg(feature = "float")]
+    pub fn measurement_variance(&self, rpt: Repeatability) -> (f32, f32) {
+        let t = self.temperature_celsius();
+        let rh = self.humidity_percent();
+        let t_outside = if t < 0.0 { -t } else if t > 90.0 { t - 90.0 } else { 0.0 };
+        let rh_outside = if rh < 10.0 { 10.0 - rh } else if rh > 90.0 { rh - 90.0 } else { 0.0 };
+
+        let t_sd = rpt.noise_temp_centi() as f32 / 100.0 * (1.0 + t_outside / 20.0);
+        let rh_sd = rpt.noise_rh_centi() as f32 / 100.0 * (1.0 + rh_outside * 0.075);
+        (t_sd * t_sd, rh_sd * rh_sd)
+    }
+
+    /// Dew point in degrees Celsius.
+    ///
+    /// Uses the Magnus formula with the coefficients from Sensirion's dew
+    /// point application note. Returns NaN at 0 %RH, where there is no dew
+    /// point.
+    #[cfg(feature = "float")]
+    pub fn dew_point_celsius(&self) -> f32 {
+        const B: f32 = 17.62;
+        const C: f32 = 243.12;
+
+        let t = self.temperature_celsius();
+        let gamma = libm::logf(self.humidity_percent() / 100.0) + B * t / (C + t);
+        C * gamma / (B - gamma)
+    }
+
+    /// Partial pressure of water vapor in hPa.
+    ///
+    /// The saturation vapor pressure from the Magnus formula, with the same
+    /// coefficients as [`dew_point_celsius`](Self::dew_point_celsius), times
+    /// the relative humidity.
+    #[cfg(feature = "float")]
+    pub fn vapor_pressure_hpa(&self) -> f32 {
+        self.saturation_vapor_pressure_hpa() * self.humidity_percent() / 100.0
+    }
+
+    /// Vapor pressure deficit (VPD) in kPa, the unit horticulture uses.
+    ///
+    /// The saturation vapor pressure minus the actual one from
+    /// [`vapor_pressure_hpa`](Self::vapor_pressure_hpa). This assumes the
+    /// leaves are at air temperature; leaf VPD needs the leaf temperature
+    /// from a separate sensor.
+    #[cfg(feature = "float")]
+    pub fn vapor_pressure_deficit_kpa(&self) -> f32 {
+        let saturation = self.saturation_vapor_pressure_hpa();
+        (saturation - saturation * self.humidity_percent() / 100.0) / 10.0
+    }
+
+    /// Saturation vapor pressure over water in hPa (Magnus formula).
+    #[cfg(feature = "float")]
+    fn saturation_vapor_pressure_hpa(&self) -> f32 {
+        let t = self.temperature_celsius();
+        6.112 * libm::expf(17.62 * t / (243.12 + t))
+    }
+
+    /// Water vapor concentration in parts per million by volume, at a total
+    /// air pressure of `pressure_hpa`.
+    ///
+    /// This is the vapor's mole fraction, `e / p * 10^6`, with `e` from
+    /// [`vapor_pressure_hpa`](Self::vapor_pressure_hpa). The pressure has to
+    /// come from a separate barometer; use 1013.25 hPa at sea level if none
+    /// is available.
+    #[cfg(feature = "float")]
+    pub fn water_vapor_ppmv(&self, pressure_hpa: f32) -> f32 {
+        self.vapor_pressure_hpa() / pressure_hpa * 1.0e6
+    }
+
+    /// Dew point in degrees Celsius, using the Arden Buck equation.
+    ///
+    /// Buck's saturation vapor pressure over water stays closer to the
+    /// reference formulations than Magnus at high temperatures, where the two
+    /// dew points drift apart by up to about 0.5 °C (at 120 °C). From -40 °C
+    /// to 50 °C they agree to within 0.05 °C. This one costs an extra
+    /// `sqrtf` over [`dew_point_celsius`](Self::dew_point_celsius).
+    /// Returns NaN at 0 %RH.
+    #[cfg(feature = "float")]
+    pub fn dew_point_celsius_buck(&self) -> f32 {
+        const B: f32 = 18.678;
+        const C: f32 = 257.14;
+        const D: f32 = 234.5;
+
+        // Buck's vapor pressure is e = 6.1121 * exp((B - T/D) * T/(C + T)).
+        // With gamma = ln(e / 6.1121) that's a quadratic in the dew point,
+        // whose smaller root is the physical one.
+        let t = self.temperature_celsius();
+        let gamma = libm::logf(self.humidity_percent() / 100.0) + (B - t / D) * t / (C + t);
+        let b = B - gamma;
+        D / 2.0 * (b - libm::sqrtf(b * b - 4.0 * C * gamma / D))
+    }
+
+    /// Apparent ("feels like") temperature in degrees Celsius.
+    ///
+    /// Steadman's formula as used by the Australian Bureau of Meteorology,
+    /// `T + 0.33 * e - 0.70 * v - 4.0`, with the vapor pressure `e` in hPa
+    /// from [`vapor_pressure_hpa`](Self::vapor_pressure_hpa) and the wind
+    /// speed `v` taken as 0 m/s, so it's the value in calm air in the shade.
+    /// It was fitted for outdoor conditions from about -10 °C to 50 °C.
+    #[cfg(feature = "float")]
+    pub fn apparent_temperature_celsius(&self) -> f32 {
+        self.temperature_celsius() + 0.33 * self.vapor_pressure_hpa() - 4.0
+    }
+
+    /// Partial pressure of the dry air in hPa, at a total air pressure of
+    /// `total_pressure_hpa`.
+    ///
+    /// Dalton's law: the total pressure minus the vapor pressure of this
+    /// reading, see [`vapor_pressure_hpa`](Self::vapor_pressure_hpa).
+    #[cfg(feature = "float")]
+    pub fn dry_air_pressure_hpa(&self, total_pressure_hpa: f32) -> f32 {
+        total_pressure_hpa - self.vapor_pressure_hpa()
+    }
+
+    /// Density of the moist air in kg/m³, at a total air pressure of
+    /// `pressure_hpa`.
+    ///
+    /// The ideal gas law for each component, `ρ = pd / (Rd * T) + e / (Rv * T)`,
+    /// with the dry air pressure `pd` from
+    /// [`dry_air_pressure_hpa`](Self::dry_air_pressure_hpa), the vapor
+    /// pressure `e`, the absolute temperature `T`, and the specific gas
+    /// constants of dry air (287.058 J/(kg·K)) and water vapor
+    /// (461.495 J/(kg·K)).
+    #[cfg(feature = "float")]
+    pub fn air_density_kg_per_m3(&self, pressure_hpa: f32) -> f32 {
+        const R_DRY: f32 = 287.058;
+        const R_VAPOR: f32 = 461.495;
+
+        let t = self.temperature_celsius() + 273.15;
+        let e = self.vapor_pressure_hpa();
+        ((pressure_hpa - e) / R_DRY + e / R_VAPOR) * 100.0 / t
+    }
+
+    /// Temperature in degrees Fahrenheit.
+    #[cfg(feature = "float")]
+    pub fn temperature_fahrenheit(&self) -> f32 {
+        self.temperature_celsius() * 1.8 + 32.0
+    }
+
+    /// Absolute humidity in grams of water vapor per cubic meter of air.
+    ///
+    /// From the ideal gas law, `216.7 * e / (273.15 + T)`, with the vapor
+    /// pressure `e` in hPa from [`vapor_pressure_hpa`](Self::vapor_pressure_hpa).
+    #[cfg(feature = "float")]
+    pub fn absolute_humidity_g_m3(&self) -> f32 {
+        absolute_humidity(self.temperature_celsius(), self.vapor_pressure_hpa())
+    }
+
+    /// Heat index in degrees Celsius, how hot it feels in the shade.
+    ///
+    /// Uses the algorithm of the US National Weather Service: the Rothfusz
+    /// regression with its low and high humidity adjustments, or Steadman's
+    /// simpler formula where the heat index is below about 27 °C (80 °F).
+    /// The regression was only fitted for warm, humid conditions, so for
+    /// cool air the result is close to the temperature and of little use.
+    #[cfg(feature = "float")]
+    pub fn heat_index_celsius(&self) -> f32 {
+        let t = self.temperature_fahrenheit();
+        let rh = self.humidity_percent();
+
+        let simple = 0.5 * (t + 61.0 + (t - 68.0) * 1.2 + rh * 0.094);
+        let hi = if (simple + t) / 2.0 < 80.0 {
+            simple
+        } else {
+            let mut hi = -42.379 + 2.049_015_2 * t + 10.143_331 * rh
+                - 0.224_755_4 * t * rh
+                - 6.837_83e-3 * t * t
+                - 5.481_717e-2 * rh * rh
+                + 1.228_74e-3 * t * t * rh
+                + 8.5282e-4 * t * rh * rh
+                - 1.99e-6 * t * t * rh * rh;
+            if rh < 13.0 && (80.0..=112.0).contains(&t) {
+                hi -= (13.0 - rh) / 4.0 * libm::sqrtf((17.0 - libm::fabsf(t - 95.0)) / 17.0);
+            } else if rh > 85.0 && (80.0..=87.0).contains(&t) {
+                hi += (rh - 85.0) / 10.0 * ((87.0 - t) / 5.0);
+            }
+            hi
+        };
+        (hi - 32.0) / 1.8
+    }
+
+    /// All derived metrics at once, see [`EnvironmentSnapshot`].
+    ///
+    /// Cheaper than calling each accessor, as the vapor pressure is only
+    /// computed once.
+    #[cfg(feature = "float")]
+    pub fn snapshot(&self) -> EnvironmentSnapshot {
+        const B: f32 = 17.62;
+        const C: f32 = 243.12;
+
+        let t = self.temperature_celsius();
+        let e = self.vapor_pressure_hpa();
+        // The Magnus formula inverted, as in `dew_point_celsius`.
+        let gamma = libm::logf(e / 6.112);
+        EnvironmentSnapshot {
+            temperature_celsius: t,
+            temperature_fahrenheit: self.temperature_fahrenheit(),
+            humidity_percent: self.humidity_percent(),
+            dew_point_celsius: C * gamma / (B - gamma),
+            absolute_humidity_g_m3: absolute_humidity(t, e),
+            heat_index_celsius: self.heat_index_celsius(),
+        }
+    }
+
+    /// Temperature minus dew point, in degrees Celsius.
+    ///
+    /// A spread near zero means condensation is likely.
+    #[cfg(feature = "float")]
+    pub fn dew_point_spread_celsius(&self) -> f32 {
+        self.temperature_celsius() - self.dew_point_celsius()
+    }
+}
+
+/// Unit of a [`Quantity`]
+///
+/// Both units are hundredths of the everyday unit, the resolution of
+/// [`Measurement`]; divide by 100 for degrees Celsius or percent. There's
+/// no conversion between the two.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Unit {
+    /// Centidegrees Celsius (0.01 °C)
+    Centidegree,
+    /// Centi-percent relative humidity (0.01 %RH)
+    Centipercent,
+}
+
+/// A value tagged with its unit, so code can check at runtime which of the
+/// two it got
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Quantity {
+    pub value: i32,
+    pub unit: Unit,
+}
+
+/// Integer representation of relative humidity, see
+/// [`Measurement::humidity_as`]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum HumidityFormat {
+    /// Centi-percent, 0 to 10000, as in [`Measurement::humidity`]
+    CentiPercent,
+    /// Whole percent, 0 to 100, see [`Measurement::humidity_percent_u8`]
+    Percent,
+    /// Fraction of 1 with 16 fractional bits, 0 to 65536 (= 1.0), rounded
+    /// to the nearest step
+    FractionQ16,
+}
+
+/// Absolute humidity in g/m³ at `t` °C and a vapor pressure of `e` hPa.
+#[cfg(feature = "float")]
+fn absolute_humidity(t: f32, e: f32) -> f32 {
+    216.7 * e / (273.15 + t)
+}
+
+/// A measurement with the metrics derived from it, for dashboards, see
+/// [`Measurement::snapshot`]
+///
+/// The humidity-derived fields ignore the slight dependence of the
+/// saturation vapor pressure on air pressure, which is well below the
+/// sensor's accuracy near sea level.
+#[cfg(feature = "float")]
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct EnvironmentSnapshot {
+    pub temperature_celsius: f32,
+    pub temperature_fahrenheit: f32,
+    pub humidity_percent: f32,
+    /// See [`Measurement::dew_point_celsius`]
+    pub dew_point_celsius: f32,
+    /// See [`Measurement::absolute_humidity_g_m3`]
+    pub absolute_humidity_g_m3: f32,
+    /// See [`Measurement::heat_index_celsius`]
+    pub heat_index_celsius: f32,
+}
+
+/// Histogram bin for `offset` from the start of the first bin, clamped to
+/// the valid bins.
+fn bin_index(offset: i64, width: i64, bins: usize) -> usize {
+    let last = bins.saturating_sub(1);
+    let i = offset / width;
+    if offset < 0 || i < 0 {
+        return 0;
+    }
+    usize::try_from(i).map_or(last, |i| i.min(last))
+}
+
+impl fmt::Display for Measurement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.temperature < 0 { "-" } else { "" };
+        let temperature = self.temperature.unsigned_abs();
+        write!(
+            f,
+            "{}{}.{:02} °C, {}.{:02} %RH",
+            sign,
+            temperature / 100,
+            temperature % 100,
+            self.humidity / 100,
+            self.humidity % 100,
+        )
+    }
+}
+
+/// A measurement that was either just taken or cached by
+/// [`Sht3x::measure_rate_limited`]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Reading {
+    Fresh(Measurement),
+    Cached(Measurement),
+}
+
+impl Reading {
+    /// The measurement, whether fresh or cached.
+    pub const fn measurement(&self) -> Measurement {
+        match *self {
+            Reading::Fresh(m) | Reading::Cached(m) => m,
+        }
+    }
+
+    /// Whether the measurement was just taken.
+    pub const fn is_fresh(&self) -> bool {
+        matches!(self, Reading::Fresh(_))
+    }
+}
+
+/// A measurement with the sensor health flags read right after it
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct HealthyMeasurement {
+    pub measurement: Measurement,
+    /// The sensor reset since the status register was last cleared
+    pub reset_detected: bool,
+    /// At least one alert is pending
+    pub alert_pending: bool,
+}
+
+/// Acquisition mode that produced a measurement
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Source {
+    /// [`Sht3x::measure_sourced`]
+    SingleShot,
+    /// [`Sht3x::fetch_sourced`] in periodic mode
+    Periodic,
+    /// [`Sht3x::fetch_sourced`] in ART mode
+    Art,
+}
+
+/// A measurement tagged with the acquisition mode that produced it
+///
+/// The modes have different noise characteristics, so this keeps logs that
+/// mix them interpretable.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SourcedMeasurement {
+    pub measurement: Measurement,
+    pub source: Source,
+}
+
+/// A measurement paired with the time it was taken
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TimedMeasurement<T> {
+    pub measurement: Measurement,
+    pub timestamp: T,
+}
+
+/// Snapshot of the sensor state for bring-up and bug reports
+#[cfg(feature = "status-parse")]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Diagnostics {
+    pub serial_number: u32,
+    pub status: Status,
+    pub measurement: Measurement,
+}
+
+#[cfg(feature = "status-parse")]
+impl fmt::Display for Diagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "serial number: {:#010x}, status: {:?}, measurement: {}",
+            self.serial_number, self.status, self.measurement,
+        )
+    }
+}
+
+/// Unconverted temperature and humidity readings, as sent by the sensor
+///
+/// Each reading is the sensor's 16-bit signal ratio, i.e. a fraction of
+/// full scale in units of 1/65535.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct RawMeasurement {
+    pub temperature: u16,
+    pub humidity: u16,
+}
+
+impl RawMeasurement {
+    const fn from_frame(frame: &[u8; 6]) -> Self {
+        RawMeasurement {
+            temperature: u16::from_be_bytes([frame[0], frame[1]]),
+            humidity: u16::from_be_bytes([frame[3], frame[4]]),
+        }
+    }
+
+    /// Temperature signal ratio in units of 1/65535 of full scale.
+    pub const fn temperature_ratio(&self) -> u16 {
+        self.temperature
+    }
+
+    /// Humidity signal ratio in units of 1/65535 of full scale.
+    pub const fn humidity_ratio(&self) -> u16 {
+        self.humidity
+    }
+
+    /// Raw count differences `(temperature, humidity)` from `reference`,
+    /// e.g. a reading taken at a known reference condition when the sensor
+    /// was installed.
+    ///
+    /// For drift tracking in the sensor's own domain: one temperature count
+    /// is about 0.0027 °C, one humidity count about 0.0015 %RH, both finer
+    /// than [`Measurement`]'s resolution.
+    pub const fn delta_from(&self, reference: &RawMeasurement) -> (i32, i32) {
+        (
+            self.temperature as i32 - reference.temperature as i32,
+            self.humidity as i32 - reference.humidity as i32,
+        )
+    }
+
+    /// Temperature signal ratio from 0.0 to 1.0, before any scaling.
+    #[cfg(feature = "float")]
+    pub fn temperature_normalized(&self) -> f32 {
+        self.temperature as f32 / 65535.0
+    }
+
+    /// Humidity signal ratio from 0.0 to 1.0, before any scaling.
+    #[cfg(feature = "float")]
+    pub fn humidity_normalized(&self) -> f32 {
+        self.humidity as f32 / 65535.0
+    }
+}
+
+/// Fixed-capacity buffer of the latest `N` measurements
+#[derive(Debug, Clone)]
+pub struct MeasurementRing<const N: usize> {
+    buf: [Measurement; N],
+    start: usize,
+    len: usize,
+}
+
+impl<const N: usize> MeasurementRing<N> {
+    /// Creates an empty ring.
+    pub const fn new() -> Self {
+        Self {
+            buf: [Measurement { temperature: 0, humidity: 0 }; N],
+            start: 0,
+            len: 0,
+        }
+    }
+
+    /// Add a measurement, overwriting the oldest one if the ring is full.
+    pub fn push(&mut self, measurement: Measurement) {
+        if N == 0 {
+            return;
+        }
+
+        if self.len < N {
+            self.buf[(self.start + self.len) % N] = measurement;
+            self.len += 1;
+        } else {
+            self.buf[self.start] = measurement;
+            self.start = (self.start + 1) % N;
+        }
+    }
+
+    /// Number of stored measurements.
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the ring holds no measurements.
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Iterate over the stored measurements, oldest first.
+    pub fn iter(&self) -> impl Iterator<Item = &Measurement> + '_ {
+        (0..self.len).map(move |i| &self.buf[(self.start + i) % N])
+    }
+}
+
+impl<const N: usize> Default for MeasurementRing<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Fixed offsets applied to every measurement, e.g. from a reference
+/// comparison
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Calibration {
+    /// Temperature offset in centidegrees Celsius
+    pub temperature_offset: i32,
+    /// Relative humidity offset in centi-percent
+    pub humidity_offset: i16,
+}
+
+impl Calibration {
+    /// Apply the offsets. Humidity is clamped to 0–100 %RH.
+    pub fn apply(&self, m: &Measurement) -> Measurement {
+        Measurement {
+            temperature: m.temperature.saturating_add(self.temperature_offset),
+            ..m.with_rh_correction(self.humidity_offset)
+        }
+    }
+}
+
+/// Exponential moving average with a weight of `1 / 2^shift` for new
+/// readings
+///
+/// The average is kept with `shift` extra fractional bits, so it converges
+/// to a constant input exactly instead of getting stuck a few counts off.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Ema {
+    shift: u8,
+    temperature: i32,
+    humidity: i32,
+    primed: bool,
+}
+
+impl Ema {
+    /// Create a filter; `shift` is capped at 15.
+    pub const fn new(shift: u8) -> Self {
+        let shift = if shift > 15 { 15 } else { shift };
+        Ema { shift, temperature: 0, humidity: 0, primed: false }
+    }
+
+    /// Feed a measurement and return the new average.
+    ///
+    /// The first measurement initializes the average.
+    pub fn update(&mut self, m: &Measurement) -> Measurement {
+        let t = m.temperature.clamp(TEMP_MIN_CENTI, TEMP_MAX_CENTI);
+        let h = m.humidity as i32;
+        if !self.primed {
+            self.temperature = t << self.shift;
+            self.humidity = h << self.shift;
+            self.primed = true;
+        } else {
+            self.temperature += t - (self.temperature >> self.shift);
+            self.humidity += h - (self.humidity >> self.shift);
+        }
+        Measurement {
+            temperature: self.temperature >> self.shift,
+            humidity: (self.humidity >> self.shift) as u16,
+        }
+    }
+
+    /// Forget the average, so the next measurement starts over.
+    pub fn reset(&mut self) {
+        self.primed = false;
+    }
+}
+
+/// Suppresses changes smaller than a threshold
+///
+/// The last reported measurement is repeated until either value has moved
+/// by at least its threshold.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Deadband {
+    temperature: i32,
+    humidity: u16,
+    last: Option<Measurement>,
+}
+
+impl Deadband {
+    /// Create a deadband with thresholds in centidegrees and centi-percent.
+    pub const fn new(temperature: i32, humidity: u16) -> Self {
+        Deadband { temperature, humidity, last: None }
+    }
+
+    /// Feed a measurement and return the one to report.
+    pub fn update(&mut self, m: &Measurement) -> Measurement {
+        match self.last {
+            Some(last)
+                if Measurement::max_disagreement(m, &last).0 < self.temperature
+                    && m.humidity.abs_diff(last.humidity) < self.humidity =>
+            {
+                last
+            }
+            _ => self.force(m),
+        }
+    }
+
+    /// Report `m` regardless of the threshold, e.g. for a user-requested
+    /// refresh.
+    pub fn force(&mut self, m: &Measurement) -> Measurement {
+        self.last = Some(*m);
+        *m
+    }
+}
+
+/// Rejects readings that changed implausibly fast since the previous one
+///
+/// Each reading is compared with the one before it, whether that one was
+/// accepted or not. A single spike therefore also rejects the first reading
+/// after it, but a real step change (e.g. moving the sensor to another
+/// room) can't lock the filter out: only the first reading after the step
+/// is rejected.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RateLimit {
+    temperature: i32,
+    humidity: u16,
+    last: Option<Measurement>,
+}
+
+impl RateLimit {
+    /// Create a rate limit with the largest plausible change between two
+    /// readings in centidegrees and centi-percent.
+    pub const fn new(max_temp_delta_centi: i32, max_rh_delta_centi: u16) -> Self {
+        RateLimit { temperature: max_temp_delta_centi, humidity: max_rh_delta_centi, last: None }
+    }
+
+    /// Feed a measurement, failing with [`Error::ImplausibleJump`] if it
+    /// moved by more than a limit since the previous one.
+    pub fn check<E>(&mut self, m: Measurement) -> Result<Measurement, Error<E>> {
+        let last = self.last.replace(m);
+        match last {
+            Some(last)
+                if Measurement::max_disagreement(&m, &last).0 > self.temperature
+                    || m.humidity.abs_diff(last.humidity) > self.humidity =>
+            {
+                Err(Error::ImplausibleJump)
+            }
+            _ => Ok(m),
+        }
+    }
+
+    /// Forget the previous reading, e.g. after a gap in sampling.
+    pub fn reset(&mut self) {
+        self.last = None;
+    }
+}
+
+/// Reusable chain of optional processing steps
+///
+/// [`process`](Pipeline::process) applies the enabled stages in this order:
+///
+/// 1. [`Calibration`]
+/// 2. Range check ([`Measurement::is_plausible`]), failing with
+///    [`Error::OutOfRange`] before the reading can affect the filters
+/// 3. [`Ema`]
+/// 4. [`Deadband`]
+///
+/// Every stage is off until it's configured, so an empty pipeline passes
+/// measurements through unchanged.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct Pipeline {
+    calibration: Option<Calibration>,
+    range_check: bool,
+    ema: Option<Ema>,
+    deadband: Option<Deadband>,
+}
+
+impl Pipeline {
+    /// Create a pipeline with every stage disabled.
+    pub const fn new() -> Self {
+        Pipeline { calibration: None, range_check: false, ema: None, deadband: None }
+    }
+
+    /// Apply calibration offsets.
+    pub const fn with_calibration(mut self, calibration: Calibration) -> Self {
+        self.calibration = Some(calibration);
+        self
+    }
+
+    /// Reject implausible measurements.
+    pub const fn with_range_check(mut self, enabled: bool) -> Self {
+        self.range_check = enabled;
+        self
+    }
+
+    /// Smooth measurements with a moving average.
+    pub const fn with_ema(mut self, ema: Ema) -> Self {
+        self.ema = Some(ema);
+        self
+    }
+
+    /// Suppress small changes.
+    pub const fn with_deadband(mut self, deadband: Deadband) -> Self {
+        self.deadband = Some(deadband);
+        self
+    }
+
+    /// Run a measurement through the pipeline.
+    pub fn process<E>(&mut self, raw: Measurement) -> Result<Measurement, Error<E>> {
+        let mut m = raw;
+        if let Some(calibration) = &self.calibration {
+            m = calibration.apply(&m);
+        }
+        if self.range_check && !m.is_plausible() {
+            return Err(Error::OutOfRange);
+        }
+        if let Some(ema) = &mut self.ema {
+            m = ema.update(&m);
+        }
+        if let Some(deadband) = &mut self.deadband {
+            m = deadband.update(&m);
+        }
+        Ok(m)
+    }
+}
+
+// 4.11 Status register
+// Table 17
+#[allow(unused)]
+mod status_bits {
+    pub const ALERT_PENDING:         u16 = 1 << 15;
+    pub const HEATER:                u16 = 1 << 13;
+    pub const RH_TRACKING_ALERT:     u16 = 1 << 11;
+    pub const T_TRACKING_ALERT:      u16 = 1 << 10;
+    pub const SYSTEM_RESET_DETECTED: u16 = 1 <<  4;
+    pub const COMMAND:               u16 = 1 <<  1;
+    pub const WRITE_DATA_CHECKSUM:   u16 = 1 <<  0;
+}
+
+#[cfg(feature = "status-parse")]
+bitflags! {
+    /// Status register
+    pub struct Status: u16 {
+        /// Alert pending status
+        const ALERT_PENDING         = status_bits::ALERT_PENDING;
+        /// Heater status
+        const HEATER                = status_bits::HEATER;
+        /// RH tracking alert
+        const RH_TRACKING_ALERT     = status_bits::RH_TRACKING_ALERT;
+        /// T tracking alert
+        const T_TRACKING_ALERT      = status_bits::T_TRACKING_ALERT;
+        /// System reset detected
+        const SYSTEM_RESET_DETECTED = status_bits::SYSTEM_RESET_DETECTED;
+        /// Command status
+        const COMMAND               = status_bits::COMMAND;
+        /// Write data checksum status
+        const WRITE_DATA_CHECKSUM   = status_bits::WRITE_DATA_CHECKSUM;
+    }
+}
+
+#[cfg(feature = "status-parse")]
+impl Status {
+    /// Flags that differ between `previous` and this status.
+    ///
+    /// A set bit in the result means the flag was either asserted or cleared
+    /// since `previous` was read.
+    pub const fn changed_since(&self, previous: &Status) -> Status {
+        Status::from_bits_truncate(self.bits() ^ previous.bits())
+    }
+
+    /// Which measurement triggered an alert, from the tracking alert bits:
+    /// [`T_TRACKING_ALERT`](Status::T_TRACKING_ALERT) (bit 10) for
+    /// temperature and [`RH_TRACKING_ALERT`](Status::RH_TRACKING_ALERT)
+    /// (bit 11) for humidity.
+    pub const fn alert_source(&self) -> AlertSource {
+        match (self.contains(Status::T_TRACKING_ALERT), self.contains(Status::RH_TRACKING_ALERT)) {
+            (false, false) => AlertSource::None,
+            (true, false) => AlertSource::Temperature,
+            (false, true) => AlertSource::Humidity,
+            (true, true) => AlertSource::Both,
+        }
+    }
+}
+
+/// Measurement that triggered an alert, see [`Status::alert_source`]
+#[cfg(feature = "status-parse")]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum AlertSource {
+    None,
+    Temperature,
+    Humidity,
+    Both,
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use embedded_hal_mock::delay::MockNoop;
+    use embedded_hal_mock::i2c::{Mock as I2cMock, Transaction};
+    use embedded_hal_mock::MockError;
+    use std::io::ErrorKind;
+    use std::vec;
+    use std::vec::Vec;
+
+    #[cfg(feature = "async")]
+    mod asynch {
+        use super::*;
+        use core::future::Future;
+        use core::pin::pin;
+        use core::task::{Context, Poll, Waker};
+        use embedded_hal_async::i2c::{ErrorKind, ErrorType, I2c, Operation};
+
+        /// Poll a future that never actually waits to completion.
+        pub fn block_on<F: Future>(f: F) -> F::Output {
+            let mut f = pin!(f);
+            let mut cx = Context::from_waker(Waker::noop());
+            loop {
+                if let Poll::Ready(output) = f.as_mut().poll(&mut cx) {
+                    return output;
+                }
+            }
+        }
+
+        /// Async bus that records writes and answers reads from a queue.
+        #[derive(Default)]
+        pub struct AsyncI2c {
+            pub writes: Vec<Vec<u8>>,
+            pub reads: Vec<Result<Vec<u8>, ErrorKind>>,
+        }
+
+        impl ErrorType for AsyncI2c {
+            type Error = ErrorKind;
+        }
+
+        impl I2c for AsyncI2c {
+            async fn transaction(&mut self, address: u8, operations: &mut [Operation<'_>]) -> Result<(), ErrorKind> {
+                assert_eq!(address, Address::Low as u8);
+                for op in operations {
+                    match op {
+                        Operation::Write(bytes) => self.writes.push(bytes.to_vec()),
+                        Operation::Read(buf) => buf.copy_from_slice(&self.reads.remove(0)?),
+                    }
+                }
+                Ok(())
+            }
+        }
+
+        /// Delay that records the requested milliseconds.
+        #[derive(Default)]
+        pub struct AsyncDelay(pub Vec<u32>);
+
+        impl DelayNs for AsyncDelay {
+            async fn delay_ns(&mut self, _ns: u32) {}
+
+            async fn delay_ms(&mut self, ms: u32) {
+                self.0.push(ms);
+            }
+        }
+    }
+
+    /// Build a valid measurement frame from raw readings.
+    fn frame(temperature: u16, humidity: u16) -> Vec<u8> {
+        expected_frame(temperature, humidity).to_vec()
+    }
+
+    /// Blocking delay that records every wait.
+    #[derive(Default)]
+    struct RecordingDelay(Vec<u8>);
+
+    impl DelayMs<u8> for RecordingDelay {
+        fn delay_ms(&mut self, ms: u8) {
+            self.0.push(ms);
+        }
+    }
+
+    /// Make a transaction fail with a generic bus error.
+    fn io_error(transaction: Transaction) -> Transaction {
+        transaction.with_error(MockError::Io(ErrorKind::Other))
+    }
+
+    /// Build a single data word with its CRC.
+    fn word_frame(word: u16) -> Vec<u8> {
+        let [w0, w1] = word.to_be_bytes();
+        vec![w0, w1, Crc::SENSIRION.checksum([w0, w1])]
+    }
+
+    /// Build a valid status register frame.
+    #[cfg(feature = "status-parse")]
+    fn status_frame(status: Status) -> Vec<u8> {
+        word_frame(status.bits())
+    }
+
+    /// Build a command followed by a data word.
+    fn command_with_data(command: u16, data: u16) -> Vec<u8> {
+        let mut bytes = command.to_be_bytes().to_vec();
+        bytes.extend(word_frame(data));
+        bytes
+    }
+
+    #[test]
+    fn test_crc() {
+        assert_eq!(Crc::SENSIRION.checksum([0xBE, 0xEF]), 0x92);
+    }
+
+    #[test]
+    fn test_crc_custom_init() {
+        let crc = Crc { init: 0x00, ..Crc::SENSIRION };
+        assert_eq!(crc.checksum([0xBE, 0xEF]), 0x13);
+
+        let frame = [0xBE, 0xEF, 0x13, 0xBE, 0xEF, 0x13];
+        assert!(parse_frame_with::<SensirionConversion>(&frame, &crc).is_ok());
+        assert_eq!(parse_frame(&frame), Err(CrcError));
+    }
+
+    #[test]
+    fn test_convert_temperature_precise() {
+        for raw in [0, 1, 0x6666, 0xFFFE, 0xFFFF] {
+            let precise = convert_temperature_precise(raw);
+            assert_eq!(precise.div_euclid(10), convert_temperature(raw));
+        }
+        assert_eq!(convert_temperature_precise(0), -45000);
+        assert_eq!(convert_temperature_precise(0xFFFF), 130000);
+    }
+
+    #[test]
+    fn test_parse_frame() {
+        let mut frame = [0x66, 0x66, 0, 0x80, 0x00, 0];
+        frame[2] = Crc::SENSIRION.checksum([frame[0], frame[1]]);
+        frame[5] = Crc::SENSIRION.checksum([frame[3], frame[4]]);
+        assert_eq!(parse_frame(&frame), Ok(Measurement { temperature: 2500, humidity: 5000 }));
+
+        frame[4] ^= 0x01;
+        assert_eq!(parse_frame(&frame), Err(CrcError));
+    }
+
+    #[test]
+    #[cfg(feature = "status-parse")]
+    fn test_status_changed_since() {
+        let idle = Status::empty();
+        let alert = Status::ALERT_PENDING | Status::T_TRACKING_ALERT;
+        let heater = Status::ALERT_PENDING | Status::HEATER;
+
+        assert_eq!(idle.changed_since(&idle), Status::empty());
+        assert_eq!(alert.changed_since(&idle), alert);
+        assert_eq!(idle.changed_since(&alert), alert);
+        assert_eq!(heater.changed_since(&alert), Status::HEATER | Status::T_TRACKING_ALERT);
+    }
+
+    #[test]
+    fn test_measure_warmed() {
+        let addr = Address::Low as u8;
+        let cmd = vec![0x24, 0x00];
+        let expectations = [
+            Transaction::write(addr, cmd.clone()),
+            Transaction::read(addr, frame(0x0000, 0x0000)),
+            Transaction::write(addr, cmd.clone()),
+            Transaction::read(addr, frame(0x0000, 0x0000)),
+            Transaction::write(addr, cmd),
+            Transaction::read(addr, frame(0x6666, 0x8000)),
+        ];
+        let mut i2c = I2cMock::new(&expectations);
+        let mut sht = Sht3x::new(i2c.clone(), Address::Low);
+
+        let m = sht.measure_warmed(ClockStretch::Disabled, Repeatability::High, 2, &mut MockNoop).unwrap();
+        assert_eq!(m, Measurement { temperature: 2500, humidity: 5000 });
+        i2c.done();
+    }
+
+    #[test]
+    fn test_short_read() {
+        let addr = Address::Low as u8;
+        let mut response = frame(0x6666, 0x8000);
+        response[3..].fill(0xFF);
+        let expectations = [
+            Transaction::write(addr, vec![0x24, 0x00]),
+            Transaction::read(addr, response),
+        ];
+        let mut i2c = I2cMock::new(&expectations);
+        let mut sht = Sht3x::new(i2c.clone(), Address::Low);
+
+        let result = sht.measure(ClockStretch::Disabled, Repeatability::High, &mut MockNoop);
+        assert!(matches!(result, Err(Error::ShortRead)));
+        i2c.done();
+    }
+
+    #[test]
+    #[cfg(feature = "status-parse")]
+    fn test_reset_and_verify() {
+        let addr = Address::Low as u8;
+        let expectations = [
+            Transaction::write(addr, vec![0x30, 0xA2]),
+            Transaction::write(addr, vec![0xF3, 0x2D]),
+            Transaction::read(addr, status_frame(Status::SYSTEM_RESET_DETECTED)),
+            Transaction::write(addr, vec![0x30, 0xA2]),
+            Transaction::write(addr, vec![0xF3, 0x2D]),
+            Transaction::read(addr, status_frame(Status::empty())),
+        ];
+        let mut i2c = I2cMock::new(&expectations);
+        let mut sht = Sht3x::new(i2c.clone(), Address::Low);
+
+        assert!(sht.reset_and_verify(&mut MockNoop).is_ok());
+        assert!(matches!(sht.reset_and_verify(&mut MockNoop), Err(Error::ResetNotDetected)));
+        i2c.done();
+    }
+
+    #[test]
+    fn test_measurement_display() {
+        let m = Measurement { temperature: -5, humidity: 4507 };
+        assert_eq!(std::format!("{}", m), "-0.05 °C, 45.07 %RH");
+    }
+
+    #[test]
+    fn test_skip_redundant_commands() {
+        let addr = Address::Low as u8;
+        let expectations = [
+            Transaction::write(addr, vec![0x30, 0x6D]),
+            Transaction::write(addr, vec![0x30, 0x66]),
+            Transaction::write(addr, vec![0x30, 0x66]),
+        ];
+        let mut i2c = I2cMock::new(&expectations);
+        let mut sht = Sht3x::new(i2c.clone(), Address::Low);
+
+        sht.stop_periodic(&mut MockNoop).unwrap();
+        sht.heater_disable(&mut MockNoop).unwrap();
+        sht.heater_enable(&mut MockNoop).unwrap();
+        sht.heater_enable(&mut MockNoop).unwrap();
+        sht.heater_disable(&mut MockNoop).unwrap();
+        sht.heater_disable(&mut MockNoop).unwrap();
+        sht.heater_disable_force(&mut MockNoop).unwrap();
+        i2c.done();
+    }
+
+    #[test]
+    fn test_alert_limit_raw_round_trip() {
+        for word in [0x0000, 0x0001, 0x0200, 0x8CCC, 0xFFFF] {
+            assert_eq!(AlertLimit::from_raw(word).to_raw(), word);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "status-parse")]
+    fn test_disable_alerts() {
+        let addr = Address::Low as u8;
+        let expectations = [
+            Transaction::write(addr, command_with_data(0x611D, 0xFFFF)),
+            Transaction::write(addr, vec![0xF3, 0x2D]),
+            Transaction::read(addr, status_frame(Status::empty())),
+            Transaction::write(addr, command_with_data(0x6116, 0xFFFF)),
+            Transaction::write(addr, vec![0xF3, 0x2D]),
+            Transaction::read(addr, status_frame(Status::empty())),
+            Transaction::write(addr, command_with_data(0x610B, 0x0000)),
+            Transaction::write(addr, vec![0xF3, 0x2D]),
+            Transaction::read(addr, status_frame(Status::empty())),
+            Transaction::write(addr, command_with_data(0x6100, 0x0000)),
+            Transaction::write(addr, vec![0xF3, 0x2D]),
+            Transaction::read(addr, status_frame(Status::empty())),
+            Transaction::write(addr, vec![0xE1, 0x1F]),
+            Transaction::read(addr, word_frame(0xFFFF)),
+            Transaction::write(addr, vec![0xE1, 0x14]),
+            Transaction::read(addr, word_frame(0xFFFF)),
+            Transaction::write(addr, vec![0xE1, 0x09]),
+            Transaction::read(addr, word_frame(0x0000)),
+            Transaction::write(addr, vec![0xE1, 0x02]),
+            Transaction::read(addr, word_frame(0x0000)),
+        ];
+        let mut i2c = I2cMock::new(&expectations);
+        let mut sht = Sht3x::new(i2c.clone(), Address::Low);
+
+        sht.disable_alerts(&mut MockNoop).unwrap();
+        assert_eq!(sht.read_alert_limits(&mut MockNoop).unwrap(), AlertLimits::DISABLED);
+        i2c.done();
+    }
+
+    #[test]
+    fn test_measure_both() {
+        let (low, high) = (Address::Low as u8, Address::High as u8);
+        let expectations = [
+            io_error(Transaction::write(low, vec![0x24, 0x00])),
+            Transaction::write(high, vec![0x24, 0x00]),
+            Transaction::read(high, frame(0x6666, 0x8000)),
+        ];
+        let mut i2c = I2cMock::new(&expectations);
+
+        let (low, high) = measure_both(&mut i2c, ClockStretch::Disabled, Repeatability::High, &mut MockNoop);
+        assert!(matches!(low, Err(Error::I2c(_))));
+        assert_eq!(high.unwrap(), Measurement { temperature: 2500, humidity: 5000 });
+        i2c.done();
+    }
+
+    #[test]
+    fn test_periodic_workflow() {
+        let addr = Address::Low as u8;
+        let fetch = vec![0xE0, 0x00];
+        let mut corrupted = frame(0x6666, 0x8000);
+        corrupted[0] ^= 0x01;
+        let expectations = [
+            Transaction::write(addr, vec![0x21, 0x30]),
+            Transaction::write(addr, fetch.clone()),
+            Transaction::read(addr, frame(0x6666, 0x8000)),
+            Transaction::write(addr, fetch.clone()),
+            io_error(Transaction::read(addr, vec![0; 6])),
+            Transaction::write(addr, fetch),
+            Transaction::read(addr, corrupted),
+            Transaction::write(addr, vec![0x30, 0x93]),
+        ];
+        let mut i2c = I2cMock::new(&expectations);
+        let mut sht = Sht3x::new(i2c.clone(), Address::Low);
+
+        sht.start_periodic(Rate::R1, Repeatability::High, &mut MockNoop).unwrap();
+        assert_eq!(sht.mode(), Mode::Periodic(Rate::R1, Repeatability::High));
+        assert_eq!(sht.fetch_data(&mut MockNoop).unwrap(), Measurement { temperature: 2500, humidity: 5000 });
+        // No new data yet: the sensor NACKs the read.
+        assert!(matches!(sht.fetch_data(&mut MockNoop), Err(Error::I2c(_))));
+        assert!(matches!(sht.fetch_data(&mut MockNoop), Err(Error::Crc)));
+        sht.stop_periodic(&mut MockNoop).unwrap();
+        assert_eq!(sht.mode(), Mode::SingleShot);
+        i2c.done();
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn test_dew_point_spread() {
+        let m = Measurement { temperature: 2500, humidity: 5000 };
+        assert!((m.dew_point_celsius() - 13.85).abs() < 0.05);
+        assert!((m.dew_point_spread_celsius() - 11.15).abs() < 0.05);
+
+        let saturated = Measurement { temperature: 2500, humidity: 10000 };
+        assert!(saturated.dew_point_spread_celsius().abs() < 0.01);
+    }
+
+    #[test]
+    fn test_measure_robust() {
+        let addr = Address::Low as u8;
+        let cmd = vec![0x24, 0x00];
+        let mut corrupted = frame(0x6666, 0x8000);
+        corrupted[5] ^= 0x01;
+        let expectations = [
+            io_error(Transaction::write(addr, cmd.clone())),
+            Transaction::write(addr, cmd.clone()),
+            Transaction::read(addr, corrupted),
+            Transaction::write(addr, cmd.clone()),
+            Transaction::read(addr, frame(0x6666, 0x8000)),
+            Transaction::write(addr, cmd),
+            io_error(Transaction::read(addr, vec![0; 6])),
+        ];
+        let mut i2c = I2cMock::new(&expectations);
+        let mut sht = Sht3x::new(i2c.clone(), Address::Low);
+
+        let m = sht.measure_robust(ClockStretch::Disabled, Repeatability::High, 2, &mut MockNoop);
+        assert_eq!(m.unwrap(), Measurement { temperature: 2500, humidity: 5000 });
+        let m = sht.measure_robust(ClockStretch::Disabled, Repeatability::High, 0, &mut MockNoop);
+        assert!(matches!(m, Err(Error::I2c(_))));
+        i2c.done();
+    }
+
+    #[test]
+    fn test_is_plausible() {
+        let m = |temperature, humidity| Measurement { temperature, humidity };
+        assert!(m(2500, 5000).is_plausible());
+        assert!(m(-4000, 10000).is_plausible());
+        assert!(m(10000, 10000).is_plausible());
+        assert!(m(12000, 5119).is_plausible());
+        assert!(m(12500, 4384).is_plausible());
+
+        assert!(!m(-4001, 5000).is_plausible());
+        assert!(!m(12501, 0).is_plausible());
+        assert!(!m(2500, 10001).is_plausible());
+        assert!(!m(12000, 10000).is_plausible());
+        assert!(!m(12000, 5120).is_plausible());
+        assert!(!m(12500, 4385).is_plausible());
+    }
+
+    #[test]
+    fn test_measurement_ring() {
+        let m = |temperature| Measurement { temperature, humidity: 0 };
+        let temperatures = |ring: &MeasurementRing<3>| ring.iter().map(|m| m.temperature).collect::<Vec<_>>();
+        let mut ring = MeasurementRing::<3>::new();
+        assert!(ring.is_empty());
+
+        ring.push(m(1));
+        ring.push(m(2));
+        assert_eq!(temperatures(&ring), [1, 2]);
+
+        ring.push(m(3));
+        ring.push(m(4));
+        ring.push(m(5));
+        assert_eq!(ring.len(), 3);
+        assert_eq!(temperatures(&ring), [3, 4, 5]);
+    }
+
+    #[test]
+    #[cfg(feature = "status-parse")]
+    fn test_alert_limit_write_checksum() {
+        let addr = Address::Low as u8;
+        let limit = AlertLimit::from_raw(0x8CCC);
+        let expectations = [
+            Transaction::write(addr, command_with_data(0x611D, 0x8CCC)),
+            Transaction::write(addr, vec![0xF3, 0x2D]),
+            Transaction::read(addr, status_frame(Status::WRITE_DATA_CHECKSUM)),
+        ];
+        let mut i2c = I2cMock::new(&expectations);
+        let mut sht = Sht3x::new(i2c.clone(), Address::Low);
+
+        let result = sht.set_alert_limit(AlertThreshold::HighSet, limit, &mut MockNoop);
+        assert!(matches!(result, Err(Error::WriteChecksum)));
+        i2c.done();
+    }
+
+    #[test]
+    fn test_custom_conversion() {
+        #[derive(Debug, Clone)]
+        struct Raw;
+
+        impl Conversion for Raw {
+            fn temperature(raw: u16) -> i32 {
+                raw as i32
+            }
+
+            fn humidity(raw: u16) -> u16 {
+                raw
+            }
+        }
+
+        let addr = Address::Low as u8;
+        let expectations = [
+            Transaction::write(addr, vec![0x24, 0x00]),
+            Transaction::read(addr, frame(0x6666, 0x8000)),
+        ];
+        let mut i2c = I2cMock::new(&expectations);
+        let mut sht = Sht3x::new(i2c.clone(), Address::Low).with_conversion::<Raw>();
+
+        let m = sht.measure(ClockStretch::Disabled, Repeatability::High, &mut MockNoop).unwrap();
+        assert_eq!(m, Measurement { temperature: 0x6666, humidity: 0x8000 });
+        i2c.done();
+    }
+
+    #[cfg(feature = "lut-convert")]
+    #[test]
+    fn test_lut_conversion() {
+        for raw in 0..=u16::MAX {
+            let temperature = LutConversion::temperature(raw) - convert_temperature(raw);
+            let humidity = LutConversion::humidity(raw) as i32 - convert_humidity(raw) as i32;
+            assert!(temperature.abs() <= 1, "temperature off by {} at {:#06x}", temperature, raw);
+            assert!(humidity.abs() <= 1, "humidity off by {} at {:#06x}", humidity, raw);
+        }
+    }
+
+    #[test]
+    fn test_heater_pulse() {
+        let addr = Address::Low as u8;
+        let expectations = [
+            Transaction::write(addr, vec![0x30, 0x6D]),
+            Transaction::write(addr, vec![0x30, 0x66]),
+            io_error(Transaction::write(addr, vec![0x30, 0x6D])),
+            Transaction::write(addr, vec![0x30, 0x66]),
+        ];
+        let mut i2c = I2cMock::new(&expectations);
+        let mut sht = Sht3x::new(i2c.clone(), Address::Low);
+
+        sht.heater_pulse(1000, &mut MockNoop).unwrap();
+        assert!(!sht.heater());
+        assert!(matches!(sht.heater_pulse(1000, &mut MockNoop), Err(Error::I2c(_))));
+        assert!(!sht.heater());
+        i2c.done();
+    }
+
+    #[test]
+    fn test_rh_correction() {
+        let m = |humidity| Measurement { temperature: 2500, humidity };
+        assert_eq!(m(5000).with_rh_correction(-250), m(4750));
+        assert_eq!(m(5000).with_rh_correction(250), m(5250));
+        assert_eq!(m(100).with_rh_correction(-101), m(0));
+        assert_eq!(m(9900).with_rh_correction(101), m(10000));
+        assert_eq!(m(10000).with_rh_correction(i16::MAX), m(10000));
+        assert_eq!(m(0).with_rh_correction(i16::MIN), m(0));
+    }
+
+    #[test]
+    fn test_measure_raw() {
+        let addr = Address::Low as u8;
+        let expectations = [
+            Transaction::write(addr, vec![0x24, 0x00]),
+            Transaction::read(addr, frame(0x6666, 0xFFFF)),
+        ];
+        let mut i2c = I2cMock::new(&expectations);
+        let mut sht = Sht3x::new(i2c.clone(), Address::Low);
+
+        let raw = sht.measure_raw(ClockStretch::Disabled, Repeatability::High, &mut MockNoop).unwrap();
+        assert_eq!(raw, RawMeasurement { temperature: 0x6666, humidity: 0xFFFF });
+        assert_eq!(raw.temperature_ratio(), 0x6666);
+        #[cfg(feature = "float")]
+        {
+            assert_eq!(raw.humidity_normalized(), 1.0);
+            assert!((raw.temperature_normalized() - 0.4).abs() < 1e-6);
+        }
+        assert_eq!(sht.read_count(), 1);
+        i2c.done();
+    }
+
+    #[test]
+    fn test_ema() {
+        let m = |temperature, humidity| Measurement { temperature, humidity };
+        let mut ema = Ema::new(2);
+        assert_eq!(ema.update(&m(2000, 4000)), m(2000, 4000));
+        assert_eq!(ema.update(&m(2400, 4400)), m(2100, 4100));
+        for _ in 0..100 {
+            ema.update(&m(2400, 4400));
+        }
+        assert_eq!(ema.update(&m(2400, 4400)), m(2400, 4400));
+        ema.reset();
+        assert_eq!(ema.update(&m(-4000, 0)), m(-4000, 0));
+    }
+
+    #[test]
+    fn test_pipeline() {
+        let m = |temperature, humidity| Measurement { temperature, humidity };
+        let mut empty = Pipeline::new();
+        assert_eq!(empty.process::<()>(m(2500, 5000)).unwrap(), m(2500, 5000));
+
+        let mut pipeline = Pipeline::new()
+            .with_calibration(Calibration { temperature_offset: -100, humidity_offset: 200 })
+            .with_range_check(true)
+            .with_ema(Ema::new(1))
+            .with_deadband(Deadband::new(50, 100));
+
+        assert_eq!(pipeline.process::<()>(m(2600, 4800)).unwrap(), m(2500, 5000));
+        // Averaged to (2540, 5020): within the deadband.
+        assert_eq!(pipeline.process::<()>(m(2680, 4840)).unwrap(), m(2500, 5000));
+        // Rejected before reaching the filters.
+        assert!(matches!(pipeline.process::<()>(m(13000, 4800)), Err(Error::OutOfRange)));
+        // Averaged to (2570, 5035).
+        assert_eq!(pipeline.process::<()>(m(2700, 4850)).unwrap(), m(2570, 5035));
+    }
+
+    #[test]
+    fn test_measure_with_raw_frame() {
+        let addr = Address::Low as u8;
+        let expectations = [
+            Transaction::write(addr, vec![0x24, 0x00]),
+            Transaction::read(addr, frame(0x6666, 0x8000)),
+        ];
+        let mut i2c = I2cMock::new(&expectations);
+        let mut sht = Sht3x::new(i2c.clone(), Address::Low);
+
+        let (m, raw) = sht.measure_with_raw_frame(ClockStretch::Disabled, Repeatability::High, &mut MockNoop).unwrap();
+        assert_eq!(m, Measurement { temperature: 2500, humidity: 5000 });
+        assert_eq!(raw.to_vec(), frame(0x6666, 0x8000));
+        i2c.done();
+    }
+
+    #[test]
+    fn test_bins() {
+        let t = |temperature| Measurement { temperature, humidity: 0 };
+        // Ten 5 °C bins from -10 °C.
+        assert_eq!(t(-1000).temp_bin(-1000, 500, 10), 0);
+        assert_eq!(t(-501).temp_bin(-1000, 500, 10), 0);
+        assert_eq!(t(-500).temp_bin(-1000, 500, 10), 1);
+        assert_eq!(t(3999).temp_bin(-1000, 500, 10), 9);
+        assert_eq!(t(4000).temp_bin(-1000, 500, 10), 9);
+        assert_eq!(t(-4500).temp_bin(-1000, 500, 10), 0);
+        assert_eq!(t(12500).temp_bin(-1000, 500, 10), 9);
+        assert_eq!(t(i32::MIN).temp_bin(i32::MAX, 1, 10), 0);
+        assert_eq!(t(i32::MAX).temp_bin(i32::MIN, 1, 10), 9);
+
+        let h = |humidity| Measurement { temperature: 0, humidity };
+        // Four 20 %RH bins from 10 %RH.
+        assert_eq!(h(0).humidity_bin(1000, 2000, 4), 0);
+        assert_eq!(h(2999).humidity_bin(1000, 2000, 4), 0);
+        assert_eq!(h(3000).humidity_bin(1000, 2000, 4), 1);
+        assert_eq!(h(8999).humidity_bin(1000, 2000, 4), 3);
+        assert_eq!(h(10000).humidity_bin(1000, 2000, 4), 3);
+        assert_eq!(h(5000).humidity_bin(0, 100, 0), 0);
+    }
+
+    #[test]
+    fn test_estimate_ready() {
+        let addr = Address::Low as u8;
+        let expectations = [
+            Transaction::write(addr, vec![0x21, 0x30]),
+        ];
+        let mut i2c = I2cMock::new(&expectations);
+        let mut sht = Sht3x::new(i2c.clone(), Address::Low);
+
+        assert!(!sht.estimate_ready(u16::MAX));
+        sht.start_periodic(Rate::R1, Repeatability::High, &mut MockNoop).unwrap();
+        assert!(!sht.estimate_ready(999));
+        assert!(sht.estimate_ready(1000));
+        i2c.done();
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn test_from_celsius_percent() {
+        let m = Measurement::from_celsius_percent(23.456, 45.674);
+        assert_eq!(m, Measurement { temperature: 2346, humidity: 4567 });
+        assert!((m.temperature_celsius() - 23.456).abs() <= 0.005);
+        assert!((m.humidity_percent() - 45.674).abs() <= 0.005);
+
+        let m = Measurement::from_celsius_percent(-0.05, 120.0);
+        assert_eq!(m, Measurement { temperature: -5, humidity: 10000 });
+        assert_eq!(Measurement::from_celsius_percent(25.0, -1.0).humidity, 0);
+    }
+
+    #[test]
+    fn test_merge_mean() {
+        let m = |temperature, humidity| Measurement { temperature, humidity };
+        assert_eq!(Measurement::merge_mean(&m(2500, 5000), &m(2600, 5200)), m(2550, 5100));
+        assert_eq!(Measurement::merge_mean(&m(-5, 1), &m(0, 0)), m(-3, 0));
+        assert_eq!(Measurement::merge_mean(&m(i32::MAX, u16::MAX), &m(i32::MAX, u16::MAX)), m(i32::MAX, u16::MAX));
+
+        assert_eq!(Measurement::max_disagreement(&m(2500, 5000), &m(2600, 4800)), (100, 200));
+        assert_eq!(Measurement::max_disagreement(&m(-4500, 0), &m(12500, 10000)), (17000, 10000));
+        assert_eq!(Measurement::max_disagreement(&m(i32::MIN, 0), &m(i32::MAX, 0)), (i32::MAX, 0));
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn test_measure_retry() {
+        use asynch::{block_on, AsyncDelay, AsyncI2c};
+        use embedded_hal_async::i2c::ErrorKind;
+
+        let mut bad = frame(0x6666, 0x8000);
+        bad[5] ^= 1;
+        let i2c = AsyncI2c {
+            reads: vec![
+                Ok(bad),
+                Ok(frame(0x6666, 0x8000)),
+                Err(ErrorKind::NoAcknowledge(embedded_hal_async::i2c::NoAcknowledgeSource::Data)),
+            ],
+            ..Default::default()
+        };
+        let mut sht = Sht3x::new(i2c, Address::Low);
+        let mut delay = AsyncDelay::default();
+
+        let m = block_on(sht.measure_retry(ClockStretch::Disabled, Repeatability::High, 3, &mut delay));
+        assert_eq!(m.unwrap(), Measurement { temperature: 2500, humidity: 5000 });
+        // Conversion wait, backoff, conversion wait.
+        assert_eq!(delay.0, [15, 1, 15]);
+
+        let m = block_on(sht.measure_retry(ClockStretch::Disabled, Repeatability::High, 3, &mut delay));
+        assert!(matches!(m, Err(Error::I2c(_))));
+        assert_eq!(sht.i2c.writes, [[0x24, 0x00]; 3]);
+        assert_eq!(sht.read_count(), 1);
+    }
+
+    #[test]
+    fn test_last_used_clock_stretch() {
+        let addr = Address::Low as u8;
+        let expectations = [
+            Transaction::write(addr, vec![0x2C, 0x06]),
+            Transaction::read(addr, frame(0x6666, 0x8000)),
+            Transaction::write(addr, vec![0x24, 0x00]),
+            Transaction::read(addr, frame(0x6666, 0x8000)),
+        ];
+        let mut i2c = I2cMock::new(&expectations);
+        let mut sht = Sht3x::new(i2c.clone(), Address::Low);
+
+        assert!(!sht.last_used_clock_stretch());
+        sht.measure(ClockStretch::Enabled, Repeatability::High, &mut MockNoop).unwrap();
+        assert!(sht.last_used_clock_stretch());
+        sht.measure(ClockStretch::Disabled, Repeatability::High, &mut MockNoop).unwrap();
+        assert!(!sht.last_used_clock_stretch());
+        i2c.done();
+    }
+
+    #[test]
+    fn test_alert_presets() {
+        for preset in [AlertPreset::IndoorComfort, AlertPreset::ColdStorage, AlertPreset::Greenhouse] {
+            let l = preset.limits();
+            assert!(l.high_set.temperature > l.high_clear.temperature, "{:?}", preset);
+            assert!(l.high_clear.temperature > l.low_clear.temperature, "{:?}", preset);
+            assert!(l.low_clear.temperature > l.low_set.temperature, "{:?}", preset);
+            assert!(l.high_set.humidity > l.high_clear.humidity, "{:?}", preset);
+            assert!(l.high_clear.humidity > l.low_clear.humidity, "{:?}", preset);
+            assert!(l.low_clear.humidity >= l.low_set.humidity, "{:?}", preset);
+        }
+    }
+
+    #[test]
+    fn test_measure_split() {
+        let addr = Address::Low as u8;
+        let expectations = [
+            Transaction::write(addr, vec![0x24, 0x00]),
+            Transaction::read(addr, frame(0x6666, 0x0000)),
+            Transaction::write(addr, vec![0x24, 0x16]),
+            Transaction::read(addr, frame(0x0000, 0x8000)),
+            Transaction::write(addr, vec![0x24, 0x0B]),
+            Transaction::read(addr, frame(0x6666, 0x8000)),
+        ];
+        let mut i2c = I2cMock::new(&expectations);
+        let mut sht = Sht3x::new(i2c.clone(), Address::Low);
+
+        let m = sht.measure_split(ClockStretch::Disabled, Repeatability::High, Repeatability::Low, &mut MockNoop).unwrap();
+        assert_eq!(m, Measurement { temperature: 2500, humidity: 5000 });
+        let m = sht.measure_split(ClockStretch::Disabled, Repeatability::Medium, Repeatability::Medium, &mut MockNoop).unwrap();
+        assert_eq!(m, Measurement { temperature: 2500, humidity: 5000 });
+        i2c.done();
+    }
+
+    #[test]
+    fn test_temperature_decidegrees() {
+        let t = |temperature| Measurement { temperature, humidity: 0 };
+        assert_eq!(t(2345).temperature_decidegrees(), 235);
+        assert_eq!(t(2344).temperature_decidegrees(), 234);
+        assert_eq!(t(-2345).temperature_decidegrees(), -235);
+        assert_eq!(t(-4).temperature_decidegrees(), 0);
+        assert_eq!(t(12500).temperature_decidegrees(), 1250);
+    }
+
+    #[test]
+    fn test_heater_sanity_check() {
+        let addr = Address::Low as u8;
+        let expectations = [
+            Transaction::write(addr, vec![0xF3, 0x2D]),
+            Transaction::read(addr, word_frame(0x0000)),
+            Transaction::write(addr, vec![0xF3, 0x2D]),
+            Transaction::read(addr, word_frame(status_bits::HEATER)),
+            Transaction::write(addr, vec![0x30, 0x6D]),
+            Transaction::write(addr, vec![0xF3, 0x2D]),
+            Transaction::read(addr, word_frame(status_bits::HEATER)),
+        ];
+        let mut i2c = I2cMock::new(&expectations);
+        let mut sht = Sht3x::new(i2c.clone(), Address::Low);
+
+        assert!(sht.heater_sanity_check(&mut MockNoop).unwrap());
+        assert!(!sht.heater_sanity_check(&mut MockNoop).unwrap());
+        sht.heater_enable(&mut MockNoop).unwrap();
+        assert!(sht.heater_sanity_check(&mut MockNoop).unwrap());
+        i2c.done();
+    }
+
+    #[test]
+    fn test_scaled_conversion() {
+        type Genuine = ScaledConversion<-4500, 17500, 10000>;
+        for raw in (0..=u16::MAX).step_by(97).chain([u16::MAX]) {
+            assert_eq!(Genuine::temperature(raw), SensirionConversion::temperature(raw));
+            assert_eq!(Genuine::humidity(raw), SensirionConversion::humidity(raw));
+        }
+
+        type Alternate = ScaledConversion<-4000, 16500, 9000>;
+        assert_eq!(Alternate::temperature(0x8000), 4250);
+        assert_eq!(Alternate::temperature(0xFFFF), 12500);
+        assert_eq!(Alternate::humidity(0x8000), 4500);
+        assert_eq!(Alternate::humidity(0xFFFF), 9000);
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn test_meteorology_inputs() {
+        let (t, rh) = Measurement { temperature: 2500, humidity: 4500 }.as_meteorology_inputs();
+        assert_eq!(t, 25.0);
+        assert_eq!(rh, 0.45);
+    }
+
+    #[test]
+    fn test_config() {
+        let addr = Address::High as u8;
+        let limits = AlertPreset::Greenhouse.limits();
+        let mut expectations = vec![];
+        for (cmd, limit) in [
+            (Command::WriteAlertLimit(AlertThreshold::HighSet), limits.high_set),
+            (Command::WriteAlertLimit(AlertThreshold::HighClear), limits.high_clear),
+            (Command::WriteAlertLimit(AlertThreshold::LowClear), limits.low_clear),
+            (Command::WriteAlertLimit(AlertThreshold::LowSet), limits.low_set),
+        ] {
+            expectations.push(Transaction::write(addr, command_with_data(cmd.value(), limit.to_raw())));
+            expectations.push(Transaction::write(addr, vec![0xF3, 0x2D]));
+            expectations.push(Transaction::read(addr, word_frame(0x0000)));
+        }
+        expectations.push(Transaction::write(addr, vec![0x2C, 0x0D]));
+        expectations.push(Transaction::read(addr, frame(0x6666, 0x8000)));
+        expectations.push(Transaction::write(addr, vec![0x30, 0xA2]));
+        let mut i2c = I2cMock::new(&expectations);
+
+        let config = Config {
+            address: addr,
+            clock_stretch: ClockStretch::Enabled,
+            repeatability: Repeatability::Medium,
+            calibration: Calibration { temperature_offset: -50, humidity_offset: 100 },
+            alert_limits: None,
+        };
+        let mut sht = Sht3x::from_config(i2c.clone(), &config).unwrap();
+        assert_eq!(sht.to_config(), config);
+
+        sht.set_alert_limits(&limits, &mut MockNoop).unwrap();
+        assert_eq!(sht.to_config(), Config { alert_limits: Some(limits), ..config });
+        let m = sht.measure_configured(&mut MockNoop).unwrap();
+        assert_eq!(m, Measurement { temperature: 2450, humidity: 5100 });
+        sht.reset(&mut MockNoop).unwrap();
+        assert_eq!(sht.to_config(), config);
+        i2c.done();
+
+        let invalid = Config { address: 0x80, ..config };
+        assert!(matches!(Sht3x::from_config(I2cMock::new(&[]), &invalid), Err(Error::InvalidAddress)));
+    }
+
+    #[test]
+    fn test_measure_rate_limited() {
+        let addr = Address::Low as u8;
+        let expectations = [
+            Transaction::write(addr, vec![0x24, 0x00]),
+            Transaction::read(addr, frame(0x6666, 0x8000)),
+            Transaction::write(addr, vec![0x24, 0x00]),
+            Transaction::read(addr, frame(0x6666, 0x0000)),
+        ];
+        let mut i2c = I2cMock::new(&expectations);
+        let mut sht = Sht3x::new(i2c.clone(), Address::Low).with_min_interval(1000);
+        let mut measure = |now| sht.measure_rate_limited(ClockStretch::Disabled, Repeatability::High, now, &mut MockNoop).unwrap();
+
+        let first = Measurement { temperature: 2500, humidity: 5000 };
+        assert_eq!(measure(u32::MAX - 100), Reading::Fresh(first));
+        assert_eq!(measure(u32::MAX), Reading::Cached(first));
+        assert_eq!(measure(898), Reading::Cached(first));
+        let second = measure(899);
+        assert!(second.is_fresh());
+        assert_eq!(second.measurement().humidity, 0);
+        i2c.done();
+    }
+
+    #[test]
+    fn test_art() {
+        let addr = Address::Low as u8;
+        let expectations = [
+            Transaction::write(addr, vec![0x2B, 0x32]),
+            Transaction::write(addr, vec![0xE0, 0x00]),
+            Transaction::read(addr, vec![0x66, 0x66, 0x93, 0x80, 0x00, 0xA2]),
+            Transaction::write(addr, vec![0x30, 0x93]),
+        ];
+        let mut i2c = I2cMock::new(&expectations);
+        let mut sht = Sht3x::new(i2c.clone(), Address::Low);
+
+        sht.start_art(&mut MockNoop).unwrap();
+        assert_eq!(sht.mode(), Mode::Art);
+        assert!(sht.estimate_ready(250));
+        let m = sht.fetch_art_data(&mut MockNoop).unwrap();
+        assert_eq!(m, Measurement { temperature: 2500, humidity: 5000 });
+        sht.stop_periodic(&mut MockNoop).unwrap();
+        assert_eq!(sht.mode(), Mode::SingleShot);
+        i2c.done();
+    }
+
+    #[test]
+    fn test_bus_bytes_per_second() {
+        assert_eq!(Rate::R0_5.bus_bytes_per_second(), 4);
+        assert_eq!(Rate::R1.bus_bytes_per_second(), 8);
+        assert_eq!(Rate::R10.bus_bytes_per_second(), 80);
+    }
+
+    #[test]
+    fn test_measure_forced() {
+        let addr = Address::Low as u8;
+        let expectations = [
+            Transaction::write(addr, vec![0x24, 0x00]),
+            Transaction::read(addr, frame(0x6666, 0x8000)),
+            Transaction::write(addr, vec![0x24, 0x00]),
+            Transaction::read(addr, frame(0x6666, 0x0000)),
+        ];
+        let mut i2c = I2cMock::new(&expectations);
+        let mut sht = Sht3x::new(i2c.clone(), Address::Low).with_min_interval(1000);
+
+        sht.measure_rate_limited(ClockStretch::Disabled, Repeatability::High, 0, &mut MockNoop).unwrap();
+        let forced = sht.measure_forced(ClockStretch::Disabled, Repeatability::High, 10, &mut MockNoop).unwrap();
+        assert_eq!(forced.humidity, 0);
+        let cached = sht.measure_rate_limited(ClockStretch::Disabled, Repeatability::High, 1009, &mut MockNoop).unwrap();
+        assert_eq!(cached, Reading::Cached(forced));
+        i2c.done();
+
+        let mut deadband = Deadband::new(100, 100);
+        let m = |temperature| Measurement { temperature, humidity: 5000 };
+        deadband.update(&m(2500));
+        assert_eq!(deadband.update(&m(2550)), m(2500));
+        assert_eq!(deadband.force(&m(2550)), m(2550));
+        assert_eq!(deadband.update(&m(2600)), m(2550));
+        assert_eq!(deadband.update(&m(i32::MIN)), m(i32::MIN));
+        assert_eq!(deadband.update(&m(i32::MAX)), m(i32::MAX));
+    }
+
+    #[test]
+    fn test_muxed() {
+        let addr = Address::Low as u8;
+        let expectations = [
+            Transaction::write(0x70, vec![1 << 0]),
+            Transaction::write(addr, vec![0x24, 0x00]),
+            Transaction::read(addr, frame(0x6666, 0x8000)),
+            io_error(Transaction::write(0x70, vec![1 << 3])),
+            Transaction::write(0x70, vec![1 << 5]),
+            Transaction::write(addr, vec![0x24, 0x00]),
+            Transaction::read(addr, frame(0x6666, 0x0000)),
+        ];
+        let i2c = I2cMock::new(&expectations);
+        let select = |i2c: &mut I2cMock, channel: u8| i2c.write(0x70, &[1 << channel]);
+        let mut mux = MuxedSht3x::new(i2c, Address::Low, [0, 3, 5], select);
+
+        let [a, b, c] = mux.read_all(ClockStretch::Disabled, Repeatability::High, &mut MockNoop);
+        assert_eq!(a.unwrap(), Measurement { temperature: 2500, humidity: 5000 });
+        assert!(matches!(b, Err(Error::I2c(_))));
+        assert_eq!(c.unwrap(), Measurement { temperature: 2500, humidity: 0 });
+        mux.release().0.done();
+
+        // Clone sensors with a different CRC.
+        let crc = Crc { init: 0x00, ..Crc::SENSIRION };
+        let [t0, t1] = 0x6666u16.to_be_bytes();
+        let [h0, h1] = 0x8000u16.to_be_bytes();
+        let clone_frame = vec![t0, t1, crc.checksum([t0, t1]), h0, h1, crc.checksum([h0, h1])];
+        let expectations = [
+            Transaction::write(0x70, vec![1 << 2]),
+            Transaction::write(addr, vec![0x24, 0x00]),
+            Transaction::read(addr, clone_frame),
+        ];
+        let mut mux = MuxedSht3x::new(I2cMock::new(&expectations), Address::Low, [2], select).with_crc(crc);
+        let [a] = mux.read_all(ClockStretch::Disabled, Repeatability::High, &mut MockNoop);
+        assert_eq!(a.unwrap(), Measurement { temperature: 2500, humidity: 5000 });
+        mux.release().0.done();
+    }
+
+    #[test]
+    fn test_energy() {
+        assert_eq!(Repeatability::High.energy_uj(), 25);
+        assert_eq!(Repeatability::Medium.energy_uj(), 9);
+        assert_eq!(Repeatability::Low.energy_uj(), 5);
+        assert_eq!(schedule_energy_uj(Repeatability::High, 60), 1485);
+        assert_eq!(schedule_energy_uj(Repeatability::Low, 3600), 17820);
+        assert_eq!(schedule_energy_uj(Repeatability::Medium, 0), 0);
+    }
+
+    #[test]
+    fn test_humidity_percent_u8() {
+        let h = |humidity| Measurement { temperature: 0, humidity };
+        assert_eq!(h(0).humidity_percent_u8(), 0);
+        assert_eq!(h(49).humidity_percent_u8(), 0);
+        assert_eq!(h(50).humidity_percent_u8(), 1);
+        assert_eq!(h(4549).humidity_percent_u8(), 45);
+        assert_eq!(h(9960).humidity_percent_u8(), 100);
+        assert_eq!(h(10000).humidity_percent_u8(), 100);
+        assert_eq!(h(u16::MAX).humidity_percent_u8(), 100);
+    }
+
+    #[test]
+    fn test_reset_preserving_config() {
+        let addr = Address::Low as u8;
+        let limits = AlertPreset::IndoorComfort.limits();
+        let mut writes = vec![];
+        for (threshold, limit) in [
+            (AlertThreshold::HighSet, limits.high_set),
+            (AlertThreshold::HighClear, limits.high_clear),
+            (AlertThreshold::LowClear, limits.low_clear),
+            (AlertThreshold::LowSet, limits.low_set),
+        ] {
+            writes.push(Transaction::write(addr, command_with_data(Command::WriteAlertLimit(threshold).value(), limit.to_raw())));
+            writes.push(Transaction::write(addr, vec![0xF3, 0x2D]));
+            writes.push(Transaction::read(addr, word_frame(0x0000)));
+        }
+
+        let mut expectations = vec![Transaction::write(addr, vec![0x30, 0x6D])];
+        expectations.extend(writes.iter().cloned());
+        expectations.push(Transaction::write(addr, vec![0x21, 0x30]));
+        // The reset itself.
+        expectations.push(Transaction::write(addr, vec![0x30, 0x93]));
+        expectations.push(Transaction::write(addr, vec![0x30, 0xA2]));
+        // Restoring the configuration.
+        expectations.push(Transaction::write(addr, vec![0x30, 0x6D]));
+        expectations.extend(writes.iter().cloned());
+        expectations.push(Transaction::write(addr, vec![0x21, 0x30]));
+        let mut i2c = I2cMock::new(&expectations);
+        let mut sht = Sht3x::new(i2c.clone(), Address::Low);
+
+        sht.heater_enable(&mut MockNoop).unwrap();
+        sht.set_alert_limits(&limits, &mut MockNoop).unwrap();
+        sht.start_periodic(Rate::R1, Repeatability::High, &mut MockNoop).unwrap();
+        sht.reset_preserving_config(&mut MockNoop).unwrap();
+
+        assert!(sht.heater());
+        assert_eq!(sht.to_config().alert_limits, Some(limits));
+        assert_eq!(sht.mode(), Mode::Periodic(Rate::R1, Repeatability::High));
+        i2c.done();
+    }
+
+    #[test]
+    fn test_sourced() {
+        let addr = Address::Low as u8;
+        let expectations = [
+            Transaction::write(addr, vec![0x24, 0x00]),
+            Transaction::read(addr, frame(0x6666, 0x8000)),
+            Transaction::write(addr, vec![0x21, 0x30]),
+            Transaction::write(addr, vec![0xE0, 0x00]),
+            Transaction::read(addr, frame(0x6666, 0x8000)),
+            Transaction::write(addr, vec![0x30, 0x93]),
+            Transaction::write(addr, vec![0x2B, 0x32]),
+            Transaction::write(addr, vec![0xE0, 0x00]),
+            Transaction::read(addr, frame(0x6666, 0x8000)),
+        ];
+        let mut i2c = I2cMock::new(&expectations);
+        let mut sht = Sht3x::new(i2c.clone(), Address::Low);
+
+        let m = sht.measure_sourced(ClockStretch::Disabled, Repeatability::High, &mut MockNoop).unwrap();
+        assert_eq!(m.source, Source::SingleShot);
+        sht.start_periodic(Rate::R1, Repeatability::High, &mut MockNoop).unwrap();
+        assert_eq!(sht.fetch_sourced(&mut MockNoop).unwrap().source, Source::Periodic);
+        sht.stop_periodic(&mut MockNoop).unwrap();
+        sht.start_art(&mut MockNoop).unwrap();
+        let m = sht.fetch_sourced(&mut MockNoop).unwrap();
+        assert_eq!(m, SourcedMeasurement { measurement: Measurement { temperature: 2500, humidity: 5000 }, source: Source::Art });
+        i2c.done();
+    }
+
+    #[test]
+    fn test_measure_worst_case_us() {
+        assert_eq!(measure_worst_case_us(Repeatability::High, 100_000), 15_940);
+        assert_eq!(measure_worst_case_us(Repeatability::High, 400_000), 15_235);
+        assert_eq!(measure_worst_case_us(Repeatability::Low, 400_000), 4_235);
+        assert_eq!(measure_worst_case_us(Repeatability::Low, 0), u32::MAX);
+    }
+
+    #[test]
+    fn test_check_words() {
+        let two_calls = |f: &[u8]| {
+            check_crc([f[0], f[1]], f[2], &Crc::SENSIRION)
+                .and(check_crc([f[3], f[4]], f[5], &Crc::SENSIRION))
+                .is_ok()
+        };
+        let valid = frame(0x6666, 0x8000);
+        assert!(check_words(&valid, &Crc::SENSIRION).is_ok());
+        assert!(two_calls(&valid));
+        for i in 0..valid.len() * 8 {
+            let mut corrupted = valid.clone();
+            corrupted[i / 8] ^= 1 << (i % 8);
+            assert!(check_words(&corrupted, &Crc::SENSIRION).is_err());
+            assert_eq!(check_words(&corrupted, &Crc::SENSIRION).is_ok(), two_calls(&corrupted));
+        }
+        assert_eq!(Crc::SENSIRION.checksum_slice(&[0xBE, 0xEF]), 0x92);
+    }
+
+    #[test]
+    fn test_measure_stable() {
+        let addr = Address::Low as u8;
+        let mut expectations = vec![];
+        for raw in [0x6666, 0x7000, 0x6666, 0x6667, 0x6666, 0x7000] {
+            expectations.push(Transaction::write(addr, vec![0x24, 0x00]));
+            expectations.push(Transaction::read(addr, frame(raw, 0x8000)));
+        }
+        let mut i2c = I2cMock::new(&expectations);
+        let mut sht = Sht3x::new(i2c.clone(), Address::Low);
+
+        // A spike, then two readings 1 centidegree apart.
+        let m = sht.measure_stable(ClockStretch::Disabled, Repeatability::High, 5, 10, 5, &mut MockNoop).unwrap();
+        assert_eq!(m, Measurement { temperature: 2500, humidity: 5000 });
+        let m = sht.measure_stable(ClockStretch::Disabled, Repeatability::High, 5, 10, 0, &mut MockNoop);
+        assert!(matches!(m, Err(Error::Unstable)));
+        i2c.done();
+    }
+
+    #[test]
+    fn test_verify_commands() {
+        let addr = Address::Low as u8;
+        let expectations = [
+            Transaction::write(addr, vec![0x30, 0x6D]),
+            Transaction::write(addr, vec![0xF3, 0x2D]),
+            Transaction::read(addr, word_frame(status_bits::HEATER)),
+            Transaction::write(addr, vec![0x30, 0x66]),
+            Transaction::write(addr, vec![0xF3, 0x2D]),
+            Transaction::read(addr, word_frame(status_bits::COMMAND)),
+        ];
+        let mut i2c = I2cMock::new(&expectations);
+        let mut sht = Sht3x::new(i2c.clone(), Address::Low).with_verify_commands(true);
+
+        sht.heater_enable(&mut MockNoop).unwrap();
+        assert!(matches!(sht.heater_disable(&mut MockNoop), Err(Error::CommandRejected)));
+        assert!(sht.heater());
+        i2c.done();
+    }
+
+    #[test]
+    fn test_measure_clock_stretch() {
+        let addr = Address::Low as u8;
+        let expectations = [
+            Transaction::write_read(addr, vec![0x2C, 0x10], frame(0x6666, 0x8000)),
+        ];
+        let mut i2c = I2cMock::new(&expectations);
+        let mut sht = Sht3x::new(i2c.clone(), Address::Low);
+
+        let m = sht.measure_clock_stretch(Repeatability::Low).unwrap();
+        assert_eq!(m, Measurement { temperature: 2500, humidity: 5000 });
+        assert!(sht.last_used_clock_stretch());
+        i2c.done();
+    }
+
+    #[cfg(feature = "status-parse")]
+    #[test]
+    fn test_alert_source() {
+        assert_eq!(Status::ALERT_PENDING.alert_source(), AlertSource::None);
+        assert_eq!(Status::T_TRACKING_ALERT.alert_source(), AlertSource::Temperature);
+        assert_eq!(Status::RH_TRACKING_ALERT.alert_source(), AlertSource::Humidity);
+        assert_eq!((Status::T_TRACKING_ALERT | Status::RH_TRACKING_ALERT).alert_source(), AlertSource::Both);
+    }
+
+    #[test]
+    fn test_parse_frame_le() {
+        let be: [u8; 6] = frame(0x6666, 0x8000).try_into().unwrap();
+        let le = [be[1], be[0], be[2], be[4], be[3], be[5]];
+        assert_eq!(parse_frame(&be).unwrap(), Measurement { temperature: 2500, humidity: 5000 });
+        assert_eq!(parse_frame_le(&le).unwrap(), Measurement { temperature: 2500, humidity: 5000 });
+        assert!(parse_frame(&le).is_err());
+        assert!(parse_frame_le(&be).is_err());
+
+        // Words made of two equal bytes read the same in both byte orders.
+        let symmetric: [u8; 6] = frame(0x6666, 0x0000).try_into().unwrap();
+        assert_eq!(parse_frame(&symmetric), parse_frame_le(&symmetric));
+    }
+
+    #[test]
+    fn test_approx_eq() {
+        let m = |temperature, humidity| Measurement { temperature, humidity };
+        let base = m(2500, 5000);
+        assert!(base.approx_eq(&m(2550, 5100), 50, 100));
+        assert!(base.approx_eq(&m(2451, 4901), 50, 100));
+        assert!(!base.approx_eq(&m(2551, 5000), 50, 100));
+        assert!(!base.approx_eq(&m(2500, 4899), 50, 100));
+        assert!(base.approx_eq(&base, 0, 0));
+        assert!(!base.approx_eq(&base, -1, 0));
+    }
+
+    #[test]
+    fn test_measure_series() {
+        let addr = Address::Low as u8;
+        let expectations = [
+            Transaction::write(addr, vec![0x24, 0x00]),
+            Transaction::read(addr, frame(0x6666, 0x8000)),
+            Transaction::write(addr, vec![0x24, 0x00]),
+            Transaction::read(addr, frame(0x6666, 0x8000)),
+            Transaction::write(addr, vec![0x24, 0x00]),
+            Transaction::read(addr, frame(0x6666, 0x8000)),
+            io_error(Transaction::write(addr, vec![0x24, 0x00])),
+        ];
+        let mut i2c = I2cMock::new(&expectations);
+        let mut sht = Sht3x::new(i2c.clone(), Address::Low);
+        let empty = TimedMeasurement { measurement: Measurement { temperature: 0, humidity: 0 }, timestamp: 0 };
+        let mut out = [empty; 3];
+
+        let mut now = 0;
+        let mut clock = || { now += 10; now };
+        let n = sht.measure_series(5, ClockStretch::Disabled, Repeatability::High, &mut MockNoop, &mut clock, &mut out[..2]).unwrap();
+        assert_eq!(n, 2);
+        assert_eq!(out.map(|m| m.timestamp), [10, 20, 0]);
+
+        let err = sht.measure_series(3, ClockStretch::Disabled, Repeatability::High, &mut MockNoop, &mut clock, &mut out);
+        assert!(matches!(err, Err((1, Error::I2c(_)))));
+        assert_eq!(out.map(|m| m.timestamp), [30, 20, 0]);
+        i2c.done();
+    }
+
+    #[cfg(feature = "bus-recovery")]
+    #[test]
+    fn test_recover_bus() {
+        use embedded_hal_mock::pin::{Mock as PinMock, State, Transaction as PinTransaction};
+
+        let pulse = || [PinTransaction::set(State::Low), PinTransaction::set(State::High)];
+        let stop = pulse();
+        let mut scl = PinMock::new(&(0..3).flat_map(|_| pulse()).collect::<Vec<_>>());
+        let mut sda = PinMock::new(&[
+            PinTransaction::get(State::Low),
+            PinTransaction::get(State::Low),
+            PinTransaction::get(State::Low),
+            PinTransaction::get(State::High),
+            stop[0].clone(),
+            stop[1].clone(),
+            PinTransaction::get(State::High),
+        ]);
+
+        assert!(matches!(check_bus(&sda), Err(Error::BusStuck)));
+        recover_bus(&mut scl, &mut sda, &mut MockNoop).unwrap();
+        scl.done();
+        sda.done();
+
+        let mut scl = PinMock::new(&(0..10).flat_map(|_| pulse()).collect::<Vec<_>>());
+        let mut sda_expectations: Vec<_> = (0..9).map(|_| PinTransaction::get(State::Low)).collect();
+        sda_expectations.extend(stop.iter().cloned());
+        sda_expectations.push(PinTransaction::get(State::Low));
+        let mut sda = PinMock::new(&sda_expectations);
+        assert!(matches!(recover_bus(&mut scl, &mut sda, &mut MockNoop), Err(Error::BusStuck)));
+        scl.done();
+        sda.done();
+    }
+
+    #[test]
+    fn test_alert_limits_display() {
+        use std::string::ToString;
+
+        let limits = AlertPreset::ColdStorage.limits();
+        assert_eq!(
+            limits.to_string(),
+            "high set 8.00 °C, 95.00 %RH; high clear 6.00 °C, 90.00 %RH; \
+             low clear 2.00 °C, 0.00 %RH; low set 0.00 °C, 0.00 %RH",
+        );
+        let stored = AlertLimit::from_raw(AlertLimit { temperature: -1000, humidity: 5000 }.to_raw());
+        assert_eq!(stored.to_string(), "-10.14 °C, 50.00 %RH");
+    }
+
+    #[test]
+    fn test_min_period_ms() {
+        assert_eq!(Repeatability::High.min_period_ms(), 15);
+        assert_eq!(Repeatability::Medium.min_period_ms(), 6);
+        assert_eq!(Repeatability::Low.min_period_ms(), 4);
+    }
+
+    #[test]
+    fn test_enter_idle() {
+        let addr = Address::Low as u8;
+        let expectations = [
+            Transaction::write(addr, vec![0x21, 0x30]),
+            Transaction::write(addr, vec![0x30, 0x6D]),
+            Transaction::write(addr, vec![0x30, 0x93]),
+            Transaction::write(addr, vec![0x30, 0x66]),
+            Transaction::write(addr, vec![0x30, 0x93]),
+            Transaction::write(addr, vec![0x30, 0x66]),
+        ];
+        let mut i2c = I2cMock::new(&expectations);
+        let mut sht = Sht3x::new(i2c.clone(), Address::Low);
+
+        sht.start_periodic(Rate::R1, Repeatability::High, &mut MockNoop).unwrap();
+        sht.heater_enable(&mut MockNoop).unwrap();
+        sht.enter_idle(&mut MockNoop).unwrap();
+        assert_eq!(sht.mode(), Mode::SingleShot);
+        assert!(!sht.heater());
+        sht.enter_idle(&mut MockNoop).unwrap();
+        i2c.done();
+    }
+
+    #[test]
+    fn test_is_saturated() {
+        let h = |humidity| Measurement { temperature: 2500, humidity };
+        assert!(!h(9900).is_saturated());
+        assert!(!h(9949).is_saturated());
+        assert!(h(9950).is_saturated());
+        assert!(h(10000).is_saturated());
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn test_measurement_variance() {
+        let close = |a: f32, b: f32| (a - b).abs() < 1e-6;
+        let m = |temperature, humidity| Measurement { temperature, humidity };
+
+        let (t, rh) = m(2500, 5000).measurement_variance(Repeatability::High);
+        assert!(close(t, 0.04 * 0.04) && close(rh, 0.08 * 0.08));
+        let (t, rh) = m(-4000, 0).measurement_variance(Repeatability::High);
+        assert!(close(t, 0.12 * 0.12) && close(rh, 0.14 * 0.14));
+        let (t, rh) = m(9000, 9000).measurement_variance(Repeatability::Low);
+        assert!(close(t, 0.15 * 0.15) && close(rh, 0.21 * 0.21));
+    }
+
+    #[test]
+    fn test_measure_healthy() {
+        let addr = Address::Low as u8;
+        let expectations = [
+            Transaction::write(addr, vec![0x24, 0x00]),
+            Transaction::read(addr, frame(0x6666, 0x8000)),
+            Transaction::write(addr, vec![0xF3, 0x2D]),
+            Transaction::read(addr, word_frame(status_bits::SYSTEM_RESET_DETECTED)),
+        ];
+        let mut i2c = I2cMock::new(&expectations);
+        let mut sht = Sht3x::new(i2c.clone(), Address::Low);
+
+        let m = sht.measure_healthy(ClockStretch::Disabled, Repeatability::High, &mut MockNoop).unwrap();
+        assert_eq!(m, HealthyMeasurement {
+            measurement: Measurement { temperature: 2500, humidity: 5000 },
+            reset_detected: true,
+            alert_pending: false,
+        });
+        i2c.done();
+    }
+
+    #[test]
+    fn test_bytes_round_trip() {
+        for temperature in (TEMP_MIN_CENTI..=TEMP_MAX_CENTI).step_by(7).chain([TEMP_MAX_CENTI]) {
+            for humidity in [RH_MIN_CENTI, 1, 4507, RH_MAX_CENTI] {
+                let m = Measurement { temperature, humidity };
+                assert_eq!(Measurement::from_bytes(&m.to_bytes()), m);
+            }
+        }
+        let m = Measurement { temperature: -5, humidity: 4507 };
+        assert_eq!(m.to_bytes(), [0xFF, 0xFB, 0x11, 0x9B]);
+        let hot = Measurement { temperature: 40000, humidity: 0 };
+        assert_eq!(Measurement::from_bytes(&hot.to_bytes()).temperature, i16::MAX as i32);
+    }
+
+    #[test]
+    fn test_tau_63_ms() {
+        let addr = Address::Low as u8;
+        let expectations = [
+            Transaction::write(addr, vec![0x2B, 0x32]),
+        ];
+        let mut i2c = I2cMock::new(&expectations);
+        let mut sht = Sht3x::new(i2c.clone(), Address::Low);
+
+        assert_eq!(sht.tau_63_ms(), Some(8000));
+        sht.start_art(&mut MockNoop).unwrap();
+        assert_eq!(sht.tau_63_ms(), None);
+        i2c.done();
+    }
+
+    #[test]
+    fn test_processed() {
+        let addr = Address::Low as u8;
+        let expectations = [
+            Transaction::write(addr, vec![0x21, 0x30]),
+            Transaction::write(addr, vec![0xE0, 0x00]),
+            Transaction::read(addr, frame(0x6666, 0x8000)),
+            Transaction::write(addr, vec![0xE0, 0x00]),
+            io_error(Transaction::read(addr, frame(0x6666, 0x8000))),
+            Transaction::write(addr, vec![0xE0, 0x00]),
+            Transaction::read(addr, frame(0x6666, 0x0000)),
+        ];
+        let mut i2c = I2cMock::new(&expectations);
+        let mut sht = Sht3x::new(i2c.clone(), Address::Low);
+        let mut delay = MockNoop;
+
+        assert!(sht.measurements(&mut delay).next().is_none());
+        sht.start_periodic(Rate::R1, Repeatability::High, &mut delay).unwrap();
+        let calibration = Calibration { temperature_offset: 100, humidity_offset: 0 };
+        let mut readings = sht.processed(&mut delay, Some(calibration), Some(Ema::new(1)));
+        assert_eq!(readings.next().unwrap().unwrap(), Measurement { temperature: 2600, humidity: 5000 });
+        assert!(matches!(readings.next(), Some(Err(Error::I2c(_)))));
+        assert_eq!(readings.next().unwrap().unwrap(), Measurement { temperature: 2600, humidity: 2500 });
+        i2c.done();
+    }
+
+    #[test]
+    fn test_measure_with_watchdog() {
+        let addr = Address::Low as u8;
+        let expectations = [
+            Transaction::write(addr, vec![0x24, 0x00]),
+            Transaction::read(addr, frame(0x6666, 0x8000)),
+        ];
+        let mut i2c = I2cMock::new(&expectations);
+        let mut sht = Sht3x::new(i2c.clone(), Address::Low);
+        let mut fed = 0;
+        let m = sht.measure_with_watchdog(ClockStretch::Disabled, Repeatability::High, &mut MockNoop, || fed += 1).unwrap();
+        assert_eq!(m, Measurement { temperature: 2500, humidity: 5000 });
+        assert_eq!(fed, 2);
+        i2c.done();
+    }
+
+    #[test]
+    #[cfg(feature = "float")]
+    fn test_alert_limit_quantize() {
+        for i in 0..=200 {
+            let percent = i as f32 / 2.0;
+            let q = AlertLimits::quantize_rh(percent);
+            assert!((q - percent).abs() < 0.8, "{} -> {}", percent, q);
+            assert_eq!(AlertLimits::quantize_rh(q), q);
+
+            let celsius = i as f32 * 0.875 - 45.0;
+            let q = AlertLimits::quantize_temp(celsius);
+            assert!((q - celsius).abs() < 0.35, "{} -> {}", celsius, q);
+            assert_eq!(AlertLimits::quantize_temp(q), q);
+        }
+    }
+
+    #[test]
+    fn test_fetch_latest_keeps_periodic() {
+        let addr = Address::Low as u8;
+        let expectations = [
+            Transaction::write(addr, vec![0x21, 0x30]),
+            Transaction::write(addr, vec![0xE0, 0x00]),
+            Transaction::read(addr, frame(0x6666, 0x8000)),
+        ];
+        let mut i2c = I2cMock::new(&expectations);
+        let mut sht = Sht3x::new(i2c.clone(), Address::Low).with_auto_break(true);
+
+        sht.start_periodic(Rate::R1, Repeatability::High, &mut MockNoop).unwrap();
+        let m = sht.fetch_latest(&mut MockNoop).unwrap();
+        assert_eq!(m, Measurement { temperature: 2500, humidity: 5000 });
+        assert_eq!(sht.mode(), Mode::Periodic(Rate::R1, Repeatability::High));
+        i2c.done();
+    }
+
+    #[test]
+    #[cfg(feature = "float")]
+    fn test_water_vapor_ppmv() {
+        let m = Measurement { temperature: 2500, humidity: 5000 };
+        assert!((m.vapor_pressure_hpa() - 15.80).abs() < 0.01);
+        assert!((m.water_vapor_ppmv(1013.25) - 15_594.0).abs() < 10.0);
+        assert!((m.water_vapor_ppmv(506.625) - 2.0 * m.water_vapor_ppmv(1013.25)).abs() < 1.0);
+        assert_eq!(Measurement { temperature: 2500, humidity: 0 }.water_vapor_ppmv(1013.25), 0.0);
+        assert!((m.dry_air_pressure_hpa(1013.25) - 997.45).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_reset_soft_only() {
+        let addr = Address::Low as u8;
+        let expectations = [
+            Transaction::write(addr, vec![0x21, 0x30]),
+            Transaction::write(addr, vec![0x30, 0x41]),
+            Transaction::write(addr, vec![0x30, 0xA2]),
+        ];
+        let mut i2c = I2cMock::new(&expectations);
+        let mut sht = Sht3x::new(i2c.clone(), Address::Low);
+
+        sht.start_periodic(Rate::R1, Repeatability::High, &mut MockNoop).unwrap();
+        sht.clear_status(&mut MockNoop).unwrap();
+        assert_eq!(sht.mode(), Mode::Periodic(Rate::R1, Repeatability::High));
+        sht.reset_soft_only(&mut MockNoop).unwrap();
+        assert_eq!(sht.mode(), Mode::SingleShot);
+        i2c.done();
+    }
+
+    #[test]
+    #[cfg(feature = "float")]
+    fn test_dew_point_buck() {
+        let m = Measurement { temperature: 2500, humidity: 5000 };
+        assert!((m.dew_point_celsius_buck() - m.dew_point_celsius()).abs() < 0.1);
+        let saturated = Measurement { temperature: -3000, humidity: 10000 };
+        assert!((saturated.dew_point_celsius_buck() + 30.0).abs() < 0.01);
+        assert!(Measurement { temperature: 2500, humidity: 0 }.dew_point_celsius_buck().is_nan());
+    }
+
+    #[test]
+    fn test_serial_number_raw() {
+        let addr = Address::Low as u8;
+        let mut response = frame(0x1234, 0x5678);
+        response[5] ^= 0x01;
+        let expectations = [
+            Transaction::write(addr, vec![0x37, 0x80]),
+            Transaction::read(addr, response.clone()),
+            Transaction::write(addr, vec![0x37, 0x80]),
+            Transaction::read(addr, response.clone()),
+        ];
+        let mut i2c = I2cMock::new(&expectations);
+        let mut sht = Sht3x::new(i2c.clone(), Address::Low);
+
+        assert!(matches!(sht.serial_number(&mut MockNoop), Err(Error::Crc)));
+        assert_eq!(sht.serial_number_raw(&mut MockNoop).unwrap()[..], response[..]);
+        i2c.done();
+    }
+
+    #[test]
+    fn test_should_report() {
+        let mut sht = Sht3x::new(I2cMock::new(&[]), Address::Low);
+        let base = Measurement { temperature: 2000, humidity: 5000 };
+
+        assert!(sht.should_report(&base, 50, 200));
+        assert!(!sht.should_report(&Measurement { temperature: 2030, humidity: 5100 }, 50, 200));
+        assert!(!sht.should_report(&Measurement { temperature: 1960, humidity: 4900 }, 50, 200));
+        assert!(!sht.should_report(&Measurement { temperature: 2050, humidity: 5200 }, 50, 200));
+        assert!(sht.should_report(&Measurement { temperature: 2051, humidity: 5000 }, 50, 200));
+        // The baseline moved to 20.51 °C.
+        assert!(!sht.should_report(&Measurement { temperature: 2080, humidity: 5000 }, 50, 200));
+        assert!(sht.should_report(&Measurement { temperature: 2080, humidity: 5201 }, 50, 200));
+        assert!(sht.should_report(&Measurement { temperature: i32::MIN, humidity: 5201 }, 50, 200));
+        assert!(sht.should_report(&Measurement { temperature: i32::MAX, humidity: 5201 }, 50, 200));
+    }
+
+    #[test]
+    fn test_status_write_read() {
+        let addr = Address::Low as u8;
+        let expectations = [Transaction::write_read(addr, vec![0xF3, 0x2D], word_frame(0x8010))];
+        let mut i2c = I2cMock::new(&expectations);
+        let mut sht = Sht3x::new(i2c.clone(), Address::Low);
+        assert_eq!(sht.status_write_read().unwrap(), 0x8010);
+        i2c.done();
+    }
+
+    #[test]
+    #[cfg(feature = "float")]
+    fn test_apparent_temperature() {
+        let m = Measurement { temperature: 2500, humidity: 5000 };
+        assert!((m.apparent_temperature_celsius() - 26.21).abs() < 0.01);
+        let dry = Measurement { temperature: 2500, humidity: 0 };
+        assert_eq!(dry.apparent_temperature_celsius(), 21.0);
+    }
+
+    #[test]
+    fn test_measure_checked_status() {
+        let addr = Address::Low as u8;
+        let mut expectations = vec![];
+        for status in [0x0010, 0x0000] {
+            expectations.extend([
+                Transaction::write(addr, vec![0x24, 0x00]),
+                Transaction::read(addr, frame(0x6666, 0x8000)),
+                Transaction::write(addr, vec![0xF3, 0x2D]),
+                Transaction::read(addr, word_frame(status)),
+            ]);
+            if status != 0 {
+                expectations.push(Transaction::write(addr, vec![0x30, 0x41]));
+            }
+        }
+        let mut i2c = I2cMock::new(&expectations);
+        let mut sht = Sht3x::new(i2c.clone(), Address::Low);
+
+        let result = sht.measure_checked_status(ClockStretch::Disabled, Repeatability::High, &mut MockNoop);
+        assert!(matches!(result, Err(Error::UnexpectedReset)));
+        let m = sht.measure_checked_status(ClockStretch::Disabled, Repeatability::High, &mut MockNoop).unwrap();
+        assert_eq!(m, Measurement { temperature: 2500, humidity: 5000 });
+        i2c.done();
+    }
+
+    #[test]
+    fn test_humidity_as() {
+        let formats = [HumidityFormat::CentiPercent, HumidityFormat::Percent, HumidityFormat::FractionQ16];
+        let expected = [(0, [0, 0, 0]), (5000, [5000, 50, 32768]), (10000, [10000, 100, 65536])];
+        for (humidity, values) in expected {
+            let m = Measurement { temperature: 2500, humidity };
+            for (format, value) in formats.iter().zip(values) {
+                assert_eq!(m.humidity_as(*format), value);
+            }
+        }
+    }
+
+    #[test]
+    fn test_rate_limit() {
+        let mut limit = RateLimit::new(100, 500);
+        let m = |temperature, humidity| Measurement { temperature, humidity };
+
+        for t in (2000..2500).step_by(100) {
+            assert!(limit.check::<()>(m(t, 5000)).is_ok());
+        }
+        assert!(matches!(limit.check::<()>(m(6400, 5000)), Err(Error::ImplausibleJump)));
+        assert!(matches!(limit.check::<()>(m(2400, 5000)), Err(Error::ImplausibleJump)));
+        assert!(limit.check::<()>(m(2400, 5500)).is_ok());
+        assert!(matches!(limit.check::<()>(m(2400, 6001)), Err(Error::ImplausibleJump)));
+        assert!(matches!(limit.check::<()>(m(i32::MIN, 6001)), Err(Error::ImplausibleJump)));
+        assert!(matches!(limit.check::<()>(m(i32::MAX, 6001)), Err(Error::ImplausibleJump)));
+    }
+
+    #[test]
+    fn test_measure_typed() {
+        let addr = Address::Low as u8;
+        let expectations = [
+            Transaction::write(addr, vec![0x24, 0x00]),
+            Transaction::read(addr, frame(0x6666, 0x8000)),
+        ];
+        let mut i2c = I2cMock::new(&expectations);
+        let mut sht = Sht3x::new(i2c.clone(), Address::Low);
+
+        let (t, rh) = sht.measure_typed(ClockStretch::Disabled, Repeatability::High, &mut MockNoop).unwrap();
+        assert_eq!(t, Quantity { value: 2500, unit: Unit::Centidegree });
+        assert_eq!(rh, Quantity { value: 5000, unit: Unit::Centipercent });
+        i2c.done();
+    }
+
+    #[test]
+    fn test_wait_for_rh_recovery() {
+        let addr = Address::Low as u8;
+        let mut expectations = vec![
+            Transaction::write(addr, vec![0x30, 0x6D]),
+            Transaction::write(addr, vec![0x30, 0x66]),
+        ];
+        for humidity in [0xB333, 0x8CCC, 0x8000, 0xB333, 0xB333] {
+            expectations.push(Transaction::write(addr, vec![0x24, 0x00]));
+            expectations.push(Transaction::read(addr, frame(0x6666, humidity)));
+        }
+        let mut i2c = I2cMock::new(&expectations);
+        let mut sht = Sht3x::new(i2c.clone(), Address::Low);
+        let baseline = Measurement { temperature: 2500, humidity: 5000 };
+
+        sht.heater_enable(&mut MockNoop).unwrap();
+        let m = sht.wait_for_rh_recovery(&baseline, 100, 2000, &mut MockNoop).unwrap();
+        assert_eq!(m.humidity, 5000);
+        assert!(!sht.heater());
+        let result = sht.wait_for_rh_recovery(&baseline, 100, 300, &mut MockNoop);
+        assert!(matches!(result, Err(Error::Unstable)));
+        i2c.done();
+    }
+
+    #[test]
+    fn test_on_measurement() {
+        use std::sync::atomic::{AtomicI32, AtomicU32, Ordering};
+        static LAST: AtomicI32 = AtomicI32::new(0);
+        static CALLS: AtomicU32 = AtomicU32::new(0);
+
+        let addr = Address::Low as u8;
+        let mut bad = frame(0x6666, 0x8000);
+        bad[2] ^= 0x01;
+        let expectations = [
+            Transaction::write(addr, vec![0x24, 0x00]),
+            Transaction::read(addr, frame(0x6666, 0x8000)),
+            Transaction::write(addr, vec![0x24, 0x00]),
+            Transaction::read(addr, frame(0x7000, 0x8000)),
+            Transaction::write(addr, vec![0x24, 0x00]),
+            Transaction::read(addr, bad),
+        ];
+        let mut i2c = I2cMock::new(&expectations);
+        let mut sht = Sht3x::new(i2c.clone(), Address::Low).with_on_measurement(|m| {
+            LAST.store(m.temperature, Ordering::Relaxed);
+            CALLS.fetch_add(1, Ordering::Relaxed);
+        });
+
+        sht.measure(ClockStretch::Disabled, Repeatability::High, &mut MockNoop).unwrap();
+        assert_eq!(LAST.load(Ordering::Relaxed), 2500);
+        let m = sht.measure_with_watchdog(ClockStretch::Disabled, Repeatability::High, &mut MockNoop, || {}).unwrap();
+        assert_eq!(LAST.load(Ordering::Relaxed), m.temperature);
+        let (_, crc_ok) = sht.measure_lenient(ClockStretch::Disabled, Repeatability::High, &mut MockNoop).unwrap();
+        assert!(!crc_ok);
+        assert_eq!(CALLS.load(Ordering::Relaxed), 2);
+        i2c.done();
+    }
+
+    #[test]
+    fn test_raw_delta_from() {
+        let reference = RawMeasurement { temperature: 0x6666, humidity: 0x8000 };
+        let drifted = RawMeasurement { temperature: 0x6670, humidity: 0x7FF0 };
+        assert_eq!(drifted.delta_from(&reference), (10, -16));
+        assert_eq!(reference.delta_from(&drifted), (-10, 16));
+        let extremes = RawMeasurement { temperature: u16::MAX, humidity: 0 };
+        let zero = RawMeasurement { temperature: 0, humidity: u16::MAX };
+        assert_eq!(extremes.delta_from(&zero), (65535, -65535));
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn test_fetch_batch() {
+        use asynch::{block_on, AsyncDelay, AsyncI2c};
+        use embedded_hal_async::i2c::{ErrorKind, NoAcknowledgeSource};
+
+        let i2c = AsyncI2c {
+            reads: vec![
+                Ok(frame(0x6666, 0x8000)),
+                Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address)),
+                Ok(frame(0x6666, 0x4000)),
+            ],
+            ..Default::default()
+        };
+        let mut sht = Sht3x::new(i2c, Address::Low);
+        let mut delay = AsyncDelay::default();
+
+        let batch: [_; 3] = block_on(sht.fetch_batch(1000, &mut delay));
+        assert_eq!(batch[0].as_ref().ok(), Some(&Measurement { temperature: 2500, humidity: 5000 }));
+        assert!(matches!(batch[1], Err(Error::I2c(_))));
+        assert_eq!(batch[2].as_ref().ok(), Some(&Measurement { temperature: 2500, humidity: 2500 }));
+        assert_eq!(delay.0, [1000, 1, 1000, 1, 1000, 1]);
+        assert_eq!(sht.i2c.writes, [[0xE0, 0x00]; 3]);
+    }
+
+    #[test]
+    fn test_raw_step() {
+        type Sensor = Sht3x<I2cMock>;
+        assert_eq!(Sensor::RAW_STEP_TEMP_MICRO, (175_000_000 + 65535 / 2) / 65535);
+        assert_eq!(Sensor::RAW_STEP_RH_MICRO, (100_000_000 + 65535 / 2) / 65535);
+    }
+
+    #[test]
+    fn test_parse_frame_from() {
+        let mut buf = frame(0x6666, 0x8000);
+        buf.extend([0xAA, 0xBB]);
+        let m = parse_frame_from::<()>(&buf).unwrap();
+        assert_eq!(m, Measurement { temperature: 2500, humidity: 5000 });
+
+        buf[4] ^= 0x01;
+        assert!(matches!(parse_frame_from::<()>(&buf), Err(Error::Crc)));
+        assert!(matches!(parse_frame_from::<()>(&buf[..5]), Err(Error::ShortRead)));
+        assert!(matches!(parse_frame_from::<()>(&[]), Err(Error::ShortRead)));
+    }
+
+    #[test]
+    #[cfg(feature = "float")]
+    fn test_snapshot() {
+        let m = Measurement { temperature: 3200, humidity: 7000 };
+        let s = m.snapshot();
+        assert_eq!(s.temperature_celsius, 32.0);
+        assert!((s.temperature_fahrenheit - 89.6).abs() < 0.001);
+        assert_eq!(s.humidity_percent, 70.0);
+        assert!((s.dew_point_celsius - m.dew_point_celsius()).abs() < 0.01);
+        // 23.6 g/m³ from psychrometric tables.
+        assert!((s.absolute_humidity_g_m3 - 23.6).abs() < 0.1);
+        // NWS heat index chart: 106 °F at 90 °F / 70 %RH.
+        let chart = Measurement::from_celsius_percent((90.0 - 32.0) / 1.8, 70.0);
+        assert!((chart.heat_index_celsius() * 1.8 + 32.0 - 106.0).abs() < 1.0);
+        assert_eq!(s.heat_index_celsius, m.heat_index_celsius());
+    }
+
+    #[test]
+    fn test_conversion_delay_blocking() {
+        let addr = Address::Low as u8;
+        let expectations = [
+            Transaction::write(addr, vec![0x24, 0x00]),
+            Transaction::read(addr, frame(0x6666, 0x8000)),
+            Transaction::write(addr, vec![0x24, 0x00]),
+            Transaction::read(addr, frame(0x6666, 0x8000)),
+        ];
+        let mut i2c = I2cMock::new(&expectations);
+        let mut sht = Sht3x::new(i2c.clone(), Address::Low);
+        let mut delay = RecordingDelay::default();
+
+        sht.measure(ClockStretch::Disabled, Repeatability::High, &mut delay).unwrap();
+        let mut sht = sht.with_conversion_delay_ms(12);
+        sht.measure(ClockStretch::Disabled, Repeatability::High, &mut delay).unwrap();
+        assert_eq!(delay.0, [15, 12]);
+        i2c.done();
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn test_conversion_delay() {
+        use asynch::{block_on, AsyncDelay, AsyncI2c};
+
+        let i2c = AsyncI2c { reads: vec![Ok(frame(0x6666, 0x8000)); 2], ..Default::default() };
+        let mut sht = Sht3x::new(i2c, Address::Low);
+        let mut delay = AsyncDelay::default();
+
+        block_on(sht.measure_async(ClockStretch::Disabled, Repeatability::High, &mut delay)).unwrap();
+        let mut sht = sht.with_conversion_delay_ms(12);
+        block_on(sht.measure_async(ClockStretch::Disabled, Repeatability::High, &mut delay)).unwrap();
+        assert_eq!(delay.0, [15, 12]);
+    }
+
+    #[test]
+    fn test_expected_frame() {
+        for (t, rh) in [(0, 0), (0x6666, 0x8000), (0xFFFF, 0xFFFF), (0x1234, 0xBEEF)] {
+            let m = parse_frame(&expected_frame(t, rh)).unwrap();
+            assert_eq!(m, Measurement { temperature: convert_temperature(t), humidity: convert_humidity(rh) });
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "display-string")]
+    fn test_to_display_string() {
+        let cases = [
+            (2345, 5149, " 23.5C  51%"),
+            (-4000, 0, "-40.0C   0%"),
+            (-4, 9960, "  0.0C 100%"),
+            (12500, 10000, "125.0C 100%"),
+            (0, 5050, "  0.0C  51%"),
+        ];
+        for (temperature, humidity, expected) in cases {
+            let m = Measurement { temperature, humidity };
+            assert_eq!(m.to_display_string::<11>(), expected);
+        }
+    }
+
+    #[test]
+    fn test_accuracy_warning() {
+        let office = Measurement { temperature: 2200, humidity: 4500 };
+        let humid = Measurement { temperature: 2200, humidity: 9500 };
+        let hot = Measurement { temperature: 7000, humidity: 4500 };
+        for variant in [SensorVariant::Sht30, SensorVariant::Sht31, SensorVariant::Sht35, SensorVariant::Unknown] {
+            assert!(!office.accuracy_warning(variant));
+        }
+        assert!(humid.accuracy_warning(SensorVariant::Sht30));
+        assert!(!humid.accuracy_warning(SensorVariant::Sht31));
+        assert!(humid.accuracy_warning(SensorVariant::Sht35));
+        assert!(hot.accuracy_warning(SensorVariant::Sht30));
+        assert!(!hot.accuracy_warning(SensorVariant::Sht31));
+        assert!(hot.accuracy_warning(SensorVariant::Sht35));
+        assert!(humid.accuracy_warning(SensorVariant::Unknown));
+        assert!(hot.accuracy_warning(SensorVariant::Unknown));
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn test_measurement_stream() {
+        use asynch::{block_on, AsyncDelay, AsyncI2c};
+        use core::future::poll_fn;
+        use core::pin::pin;
+        use futures_core::Stream;
+
+        let i2c = AsyncI2c { reads: vec![Ok(frame(0x6666, 0x8000)), Ok(frame(0x6666, 0x4000))], ..Default::default() };
+        let mut sht = Sht3x::new(i2c, Address::Low);
+        let mut delay = AsyncDelay::default();
+
+        {
+            let mut stream = pin!(sht.measurement_stream(&mut delay));
+            assert!(block_on(poll_fn(|cx| stream.as_mut().poll_next(cx))).is_none());
+        }
+        block_on(sht.start_periodic_async(Rate::R2, Repeatability::High, &mut delay)).unwrap();
+        {
+            let mut stream = pin!(sht.measurement_stream(&mut delay));
+            let mut next = || block_on(poll_fn(|cx| stream.as_mut().poll_next(cx))).unwrap().unwrap();
+            assert_eq!(next(), Measurement { temperature: 2500, humidity: 5000 });
+            assert_eq!(next(), Measurement { temperature: 2500, humidity: 2500 });
+        }
+        assert_eq!(delay.0, [1, 500, 1, 500, 1]);
+        assert_eq!(sht.i2c.writes, [[0x22, 0x36], [0xE0, 0x00], [0xE0, 0x00]]);
+    }
+
+    #[test]
+    #[cfg(feature = "float")]
+    fn test_air_density() {
+        // 1.199 kg/m³ at 20 °C, 50 %RH and 1013 hPa.
+        let m = Measurement { temperature: 2000, humidity: 5000 };
+        assert!((m.air_density_kg_per_m3(1013.0) - 1.199).abs() < 0.002);
+        // Humid air is lighter than dry air.
+        let dry = Measurement { temperature: 2000, humidity: 0 };
+        assert!(dry.air_density_kg_per_m3(1013.0) > m.air_density_kg_per_m3(1013.0));
+    }
+
+    #[test]
+    fn test_measure_escalating() {
+        let addr = Address::Low as u8;
+        let mut bad = frame(0x6666, 0x8000);
+        bad[2] ^= 0x01;
+        let expectations = [
+            Transaction::write(addr, vec![0x24, 0x16]),
+            Transaction::read(addr, bad.clone()),
+            Transaction::write(addr, vec![0x24, 0x0B]),
+            Transaction::read(addr, bad),
+            Transaction::write(addr, vec![0x24, 0x00]),
+            Transaction::read(addr, frame(0x6666, 0x8000)),
+        ];
+        let mut i2c = I2cMock::new(&expectations);
+        let mut sht = Sht3x::new(i2c.clone(), Address::Low);
+
+        let (m, rpt) = sht.measure_escalating(ClockStretch::Disabled, &mut MockNoop).unwrap();
+        assert_eq!(m, Measurement { temperature: 2500, humidity: 5000 });
+        assert_eq!(rpt, Repeatability::High);
+        i2c.done();
+    }
+
+    #[test]
+    fn test_debug_state() {
+        let addr = Address::Low as u8;
+        let expectations = [
+            Transaction::write(addr, vec![0x30, 0x6D]),
+            Transaction::write(addr, vec![0x2C, 0x06]),
+            Transaction::read(addr, frame(0x6666, 0x8000)),
+        ];
+        let mut i2c = I2cMock::new(&expectations);
+        let mut sht = Sht3x::new(i2c.clone(), Address::Low);
+
+        sht.heater_enable(&mut MockNoop).unwrap();
+        sht.measure(ClockStretch::Enabled, Repeatability::High, &mut MockNoop).unwrap();
+        assert_eq!(
+            sht.debug_state(),
+            DriverState {
+                address: addr,
+                mode: Mode::SingleShot,
+                heater: true,
+                last_used_clock_stretch: true,
+                read_count: 1,
+                alert_limits: None,
+            }
+        );
+        i2c.done();
+    }
+
+    #[test]
+    fn test_detect() {
+        let low = Address::Low as u8;
+        let high = Address::High as u8;
+        let expectations = [
+            io_error(Transaction::write(low, vec![0xF3, 0x2D])),
+            Transaction::write(high, vec![0xF3, 0x2D]),
+            Transaction::read(high, word_frame(0x0000)),
+            Transaction::write(high, vec![0x24, 0x00]),
+            Transaction::read(high, frame(0x6666, 0x8000)),
+        ];
+        let mut i2c = I2cMock::new(&expectations);
+
+        let address = detect(&mut i2c, &mut MockNoop).unwrap();
+        assert_eq!(address.address() as u8, high);
+        let mut sht = Sht3x::new_probed(i2c.clone(), address);
+        sht.measure(ClockStretch::Disabled, Repeatability::High, &mut MockNoop).unwrap();
+        i2c.done();
+    }
+
+    #[test]
+    fn test_measure_polled() {
+        let addr = Address::Low as u8;
+        let nack = || io_error(Transaction::read(addr, vec![0; 6]));
+        let expectations = [
+            Transaction::write(addr, vec![0x24, 0x00]),
+            nack(),
+            Transaction::read(addr, frame(0x6666, 0x8000)),
+            Transaction::write(addr, vec![0x24, 0x00]),
+            nack(),
+            nack(),
+        ];
+        let mut i2c = I2cMock::new(&expectations);
+        let mut sht = Sht3x::new(i2c.clone(), Address::Low);
+
+        let m = sht.measure_polled(Repeatability::High, &mut MockNoop, || false).unwrap();
+        assert_eq!(m, Measurement { temperature: 2500, humidity: 5000 });
+        let mut polls = 0;
+        let result = sht.measure_polled(Repeatability::High, &mut MockNoop, || {
+            polls += 1;
+            polls > 1
+        });
+        assert!(matches!(result, Err(Error::Timeout)));
+        i2c.done();
+    }
+
+    #[test]
+    fn test_mold_risk_score() {
+        let m = |temperature, humidity| Measurement { temperature, humidity };
+        assert_eq!(m(2200, 4500).mold_risk_score(), 0);
+        assert_eq!(m(2200, 7000).mold_risk_score(), 0);
+        assert_eq!(m(2500, 8000).mold_risk_score(), 40);
+        assert_eq!(m(2500, 9500).mold_risk_score(), 100);
+        // Colder air needs more humidity.
+        assert_eq!(m(500, 8000).mold_risk_score(), 0);
+        assert_eq!(m(500, 9000).mold_risk_score(), 35);
+        assert_eq!(m(-500, 10000).mold_risk_score(), 0);
+    }
+
+    #[test]
+    fn test_measure_lenient() {
+        let addr = Address::Low as u8;
+        let mut bad = frame(0x6666, 0x8000);
+        bad[5] ^= 0x01;
+        let expectations = [
+            Transaction::write(addr, vec![0x24, 0x00]),
+            Transaction::read(addr, frame(0x6666, 0x8000)),
+            Transaction::write(addr, vec![0x24, 0x00]),
+            Transaction::read(addr, bad),
+        ];
+        let mut i2c = I2cMock::new(&expectations);
+        let mut sht = Sht3x::new(i2c.clone(), Address::Low);
+        let expected = Measurement { temperature: 2500, humidity: 5000 };
+
+        let result = sht.measure_lenient(ClockStretch::Disabled, Repeatability::High, &mut MockNoop).unwrap();
+        assert_eq!(result, (expected, true));
+        let result = sht.measure_lenient(ClockStretch::Disabled, Repeatability::High, &mut MockNoop).unwrap();
+        assert_eq!(result, (expected, false));
+        assert_eq!(sht.read_count(), 1);
+        i2c.done();
+    }
+
+    #[test]
+    fn test_error_counters() {
+        let addr = Address::Low as u8;
+        let mut bad = frame(0x6666, 0x8000);
+        bad[2] ^= 0x01;
+        let expectations = [
+            Transaction::write(addr, vec![0x24, 0x00]),
+            Transaction::read(addr, bad),
+            io_error(Transaction::write(addr, vec![0x24, 0x00])),
+            Transaction::write(addr, vec![0xF3, 0x2D]),
+            Transaction::read(addr, vec![0x00, 0x00, 0x00]),
+        ];
+        let mut i2c = I2cMock::new(&expectations);
+        let mut sht = Sht3x::new(i2c.clone(), Address::Low);
+
+        assert!(sht.measure(ClockStretch::Disabled, Repeatability::High, &mut MockNoop).is_err());
+        assert!(sht.measure(ClockStretch::Disabled, Repeatability::High, &mut MockNoop).is_err());
+        assert!(sht.status_raw(&mut MockNoop).is_err());
+        assert_eq!((sht.crc_error_count(), sht.i2c_error_count()), (2, 1));
+        sht.reset_counters();
+        assert_eq!((sht.crc_error_count(), sht.i2c_error_count()), (0, 0));
+        i2c.done();
+    }
+
+    #[test]
+    fn test_auto_recover_periodic() {
+        let addr = Address::Low as u8;
+        let fetch = || Transaction::write(addr, vec![0xE0, 0x00]);
+        let expectations = [
+            Transaction::write(addr, vec![0x21, 0x30]),
+            fetch(),
+            Transaction::read(addr, frame(0x6666, 0x8000)),
+            Transaction::write(addr, vec![0xF3, 0x2D]),
+            Transaction::read(addr, word_frame(0x0010)),
+            Transaction::write(addr, vec![0x30, 0x41]),
+            Transaction::write(addr, vec![0x21, 0x30]),
+            fetch(),
+            Transaction::read(addr, frame(0x6666, 0x4000)),
+            fetch(),
+            Transaction::read(addr, frame(0x6666, 0x8000)),
+        ];
+        let mut i2c = I2cMock::new(&expectations);
+        let mut sht = Sht3x::new(i2c.clone(), Address::Low).with_auto_recover_periodic(2);
+
+        sht.start_periodic(Rate::R1, Repeatability::High, &mut MockNoop).unwrap();
+        for _ in 0..3 {
+            sht.fetch_data(&mut MockNoop).unwrap();
+        }
+        i2c.done();
+    }
+
+    #[test]
+    #[cfg(feature = "float")]
+    fn test_crosses_condensation() {
+        let m = |temperature, humidity| Measurement { temperature, humidity };
+        // Drying: the spread grows.
+        assert!(!crosses_condensation(&m(2000, 9500), &m(2100, 8500)));
+        // Cooling towards the dew point: 20 °C at 95 %RH has a spread of
+        // about 0.8 °C; at 97.5 %RH it's about 0.4 °C.
+        assert!(crosses_condensation(&m(2000, 9500), &m(1950, 9750)));
+        // Already there: no new alarm.
+        assert!(!crosses_condensation(&m(1950, 9750), &m(1950, 9900)));
+    }
+
+    #[test]
+    fn test_configure_rollback() {
+        let addr = Address::Low as u8;
+        let limits = AlertPreset::IndoorComfort.limits();
+        let mut good = vec![Transaction::write(addr, vec![0x30, 0xA2])];
+        for (command, limit) in [(0x611D, limits.high_set), (0x6116, limits.high_clear), (0x610B, limits.low_clear), (0x6100, limits.low_set)] {
+            good.push(Transaction::write(addr, command_with_data(command, limit.to_raw())));
+            good.push(Transaction::write(addr, vec![0xF3, 0x2D]));
+            good.push(Transaction::read(addr, word_frame(0x0000)));
+        }
+        good.push(Transaction::write(addr, vec![0x21, 0x30]));
+
+        let mut expectations = good.clone();
+        expectations.extend([
+            // Break, reset, then the second alert limit write fails.
+            Transaction::write(addr, vec![0x30, 0x93]),
+            good[0].clone(),
+            good[1].clone(),
+            good[2].clone(),
+            good[3].clone(),
+            io_error(Transaction::write(addr, command_with_data(0x6116, limits.high_clear.to_raw()))),
+            // Rollback.
+            Transaction::write(addr, vec![0x30, 0x93]),
+            Transaction::write(addr, vec![0x30, 0xA2]),
+        ]);
+        let mut i2c = I2cMock::new(&expectations);
+        let mut sht = Sht3x::new(i2c.clone(), Address::Low);
+        let config = Config { alert_limits: Some(limits), ..sht.to_config() };
+
+        sht.configure(&config, Some(Rate::R1), &mut MockNoop).unwrap();
+        assert_eq!(sht.mode(), Mode::Periodic(Rate::R1, Repeatability::High));
+        let stretched = Config { clock_stretch: ClockStretch::Enabled, ..config };
+        let result = sht.configure(&stretched, Some(Rate::R1), &mut MockNoop);
+        assert!(matches!(result, Err(Error::I2c(_))));
+        assert_eq!(sht.mode(), Mode::SingleShot);
+        assert_eq!(sht.to_config(), Config { alert_limits: None, ..config });
+        i2c.done();
+    }
+
+    #[test]
+    fn test_measure_with_buffer() {
+        let addr = Address::Low as u8;
+        let expectations = [
+            Transaction::write(addr, vec![0x24, 0x00]),
+            Transaction::read(addr, frame(0x6666, 0x8000)),
+        ];
+        let mut i2c = I2cMock::new(&expectations);
+        let mut sht = Sht3x::new(i2c.clone(), Address::Low);
+        let mut buf = [0xff; 6];
+
+        let m = sht.measure_with_buffer(ClockStretch::Disabled, Repeatability::High, &mut MockNoop, &mut buf).unwrap();
+        assert_eq!(m, Measurement { temperature: 2500, humidity: 5000 });
+        assert_eq!(buf.to_vec(), frame(0x6666, 0x8000));
+        i2c.done();
+    }
+
+    #[test]
+    fn test_last_status_raw() {
+        let addr = Address::Low as u8;
+        let expectations = [
+            Transaction::write(addr, vec![0xF3, 0x2D]),
+            Transaction::read(addr, word_frame(0x8010)),
+        ];
+        let mut i2c = I2cMock::new(&expectations);
+        let mut sht = Sht3x::new(i2c.clone(), Address::Low);
+
+        assert_eq!(sht.last_status_raw(), None);
+        assert_eq!(sht.status_raw(&mut MockNoop).unwrap(), 0x8010);
+        assert_eq!(sht.last_status_raw(), Some(0x8010));
+        i2c.done();
+    }
+
+    #[test]
+    #[cfg(feature = "float")]
+    fn test_vapor_pressure_deficit_kpa() {
+        // A typical greenhouse at 25 °C and 60 %RH.
+        let m = Measurement { temperature: 2500, humidity: 6000 };
+        assert!((m.vapor_pressure_deficit_kpa() - 1.264).abs() < 0.001);
+        assert!(Measurement { temperature: 2500, humidity: 10000 }.vapor_pressure_deficit_kpa().abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_address_other() {
+        assert_eq!(Address::Low.other() as u8, Address::High as u8);
+        assert_eq!(Address::High.other() as u8, Address::Low as u8);
+    }
+
+    #[test]
+    fn test_rounded_display() {
+        assert_eq!(Measurement { temperature: 2345, humidity: 5150 }.rounded_display(), (235, 52));
+        assert_eq!(Measurement { temperature: 2344, humidity: 5149 }.rounded_display(), (234, 51));
+        assert_eq!(Measurement { temperature: -1235, humidity: 9960 }.rounded_display(), (-124, 100));
+        assert_eq!(Measurement { temperature: -4, humidity: 0 }.rounded_display(), (0, 0));
+        assert_eq!(Measurement { temperature: i32::MIN, humidity: 0 }.rounded_display(), (i16::MIN, 0));
+    }
+
+    #[test]
+    fn test_periodic_both() {
+        let (low, high) = (Address::Low as u8, Address::High as u8);
+        let expectations = [
+            Transaction::write(low, vec![0x21, 0x30]),
+            Transaction::write(high, vec![0x21, 0x30]),
+            Transaction::write(low, vec![0xE0, 0x00]),
+            Transaction::read(low, frame(0x6666, 0x8000)),
+            Transaction::write(high, vec![0xE0, 0x00]),
+            io_error(Transaction::read(high, vec![0; 6])),
+        ];
+        let mut i2c = I2cMock::new(&expectations);
+
+        let (low, high) = start_periodic_both(&mut i2c, Rate::R1, Repeatability::High, &mut MockNoop);
+        assert!(low.is_ok() && high.is_ok());
+        let (low, high) = fetch_both(&mut i2c, &mut MockNoop);
+        assert_eq!(low.unwrap(), Measurement { temperature: 2500, humidity: 5000 });
+        assert!(matches!(high, Err(Error::I2c(_))));
+        i2c.done();
+    }
+
+    #[test]
+    #[cfg(feature = "dry-run")]
+    fn test_dry_run() {
+        let canned = Measurement { temperature: 2500, humidity: 5000 };
+        let mut sht = Sht3x::new(NoBus, Address::Low).with_dry_run(canned);
+
+        assert_eq!(sht.measure(ClockStretch::Disabled, Repeatability::High, &mut MockNoop).unwrap(), canned);
+        sht.start_periodic(Rate::R1, Repeatability::High, &mut MockNoop).unwrap();
+        assert_eq!(sht.fetch_data(&mut MockNoop).unwrap(), canned);
+        assert_eq!(sht.status_raw(&mut MockNoop).unwrap(), 0);
+        assert_eq!(sht.dry_run_log(), [0x2400, 0x2130, 0xE000, 0xF32D]);
+        assert_eq!(sht.mode(), Mode::Periodic(Rate::R1, Repeatability::High));
+
+        sht.clear_dry_run_log();
+        assert!(sht.dry_run_log().is_empty());
+        assert!(matches!(
+            Sht3x::new(NoBus, Address::Low).status_raw(&mut MockNoop),
+            Err(Error::ShortRead)
+        ));
+    }
+
+    #[test]
+    fn test_time_to_threshold() {
+        let at = |humidity| Measurement { temperature: 2500, humidity };
+        assert_eq!(time_to_threshold(&at(5000), &at(6000), 60_000, 7000), Some(60_000));
+        assert_eq!(time_to_threshold(&at(6000), &at(5000), 60_000, 2000), Some(180_000));
+        assert_eq!(time_to_threshold(&at(6000), &at(5000), 60_000, 7000), None);
+        assert_eq!(time_to_threshold(&at(5000), &at(5000), 60_000, 7000), None);
+        assert_eq!(time_to_threshold(&at(5000), &at(7500), 60_000, 7000), None);
+        assert_eq!(time_to_threshold(&at(5000), &at(7000), 60_000, 7000), Some(0));
+        assert_eq!(time_to_threshold(&at(5000), &at(5001), u32::MAX, 10000), Some(u32::MAX));
+    }
+
+    #[test]
+    fn test_measure_settled() {
+        let addr = Address::Low as u8;
+        let measure = vec![0x24, 0x00];
+        let expectations = [
+            Transaction::write(addr, measure.clone()),
+            Transaction::read(addr, frame(0x6666, 0x6000)),
+            Transaction::write(addr, measure.clone()),
+            Transaction::read(addr, frame(0x6666, 0x8000)),
+            Transaction::write(addr, measure.clone()),
+            Transaction::read(addr, frame(0x6666, 0x8000)),
+            Transaction::write(addr, measure.clone()),
+            Transaction::read(addr, frame(0x6666, 0x8000)),
+        ];
+        let mut i2c = I2cMock::new(&expectations);
+        let mut sht = Sht3x::new(i2c.clone(), Address::Low);
+        let settled = Measurement { temperature: 2500, humidity: 5000 };
+
+        let result = sht.measure_settled(ClockStretch::Disabled, Repeatability::High, 1000, Some((10, 100)), &mut MockNoop);
+        assert!(matches!(result, Err(Error::NotSettled)));
+        let result = sht.measure_settled(ClockStretch::Disabled, Repeatability::High, 1000, Some((10, 100)), &mut MockNoop);
+        assert_eq!(result.unwrap(), settled);
+        i2c.done();
+    }
+
+    #[test]
+    fn test_const_conversions() {
+        const RAW: (u16, u16) = (temperature_to_raw(2500), humidity_to_raw(5000));
+        const TABLE: [i32; 3] = [convert_temperature(0), convert_temperature(0x6666), convert_temperature(u16::MAX)];
+        const RH: u16 = convert_humidity(humidity_to_raw(10000));
+
+        assert_eq!(RAW, (0x6666, 0x8000));
+        assert_eq!(TABLE, [-4500, 2500, 13000]);
+        assert_eq!(convert_temperature(1), -4500);
+        assert_eq!(RH, 10000);
+        assert_eq!(temperature_to_raw(-10_000), 0);
+        assert_eq!(humidity_to_raw(u16::MAX), u16::MAX);
+    }
+
+    #[test]
+    fn test_unknown_variant_accuracy() {
+        assert_eq!(SensorVariant::Unknown.accuracy_rh_centi(), SensorVariant::Sht30.accuracy_rh_centi());
+        assert_eq!(SensorVariant::Unknown.accuracy_temp_centi(), SensorVariant::Sht30.accuracy_temp_centi());
+    }
+
+    #[test]
+    fn test_repeatability_accuracy() {
+        for rpt in [Repeatability::Low, Repeatability::Medium, Repeatability::High] {
+            assert_eq!((rpt.accuracy_rh_centi(), rpt.accuracy_temp_centi()), (200, 20));
+            assert!(rpt.noise_rh_centi() < rpt.accuracy_rh_centi());
+        }
+    }
+
+    #[test]
+    fn test_short_read_custom_crc() {
+        let addr = Address::Low as u8;
+        // With this CRC an all-0xFF word is valid.
+        let crc = Crc { init: 0x00, polynomial: 0x11 };
+        assert_eq!(crc.checksum([0xFF, 0xFF]), 0xFF);
+        let expectations = [
+            Transaction::write(addr, vec![0xF3, 0x2D]),
+            Transaction::read(addr, vec![0xFF; 3]),
+        ];
+        let mut i2c = I2cMock::new(&expectations);
+        let mut sht = Sht3x::new(i2c.clone(), Address::Low).with_crc(crc);
+
+        assert_eq!(sht.status_raw(&mut MockNoop).unwrap(), 0xFFFF);
+        i2c.done();
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn test_verify_commands_async() {
+        use asynch::{block_on, AsyncDelay, AsyncI2c};
+
+        let reads = vec![Ok(word_frame(0x0000)), Ok(frame(0x6666, 0x8000)), Ok(word_frame(0x0002))];
+        let i2c = AsyncI2c { reads, ..Default::default() };
+        let mut sht = Sht3x::new(i2c, Address::Low).with_auto_break(true).with_verify_commands(true);
+        let mut delay = AsyncDelay::default();
+
+        let m = block_on(sht.measure_async(ClockStretch::Disabled, Repeatability::High, &mut delay)).unwrap();
+        assert_eq!(m, Measurement { temperature: 2500, humidity: 5000 });
+        let result = block_on(sht.measure_async(ClockStretch::Disabled, Repeatability::High, &mut delay));
+        assert!(matches!(result, Err(Error::CommandRejected)));
+        assert_eq!(sht.i2c.writes, [[0x30, 0x93], [0xF3, 0x2D], [0x24, 0x00], [0x30, 0x93], [0xF3, 0x2D]]);
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn test_start_periodic_async_rejected() {
+        use asynch::{block_on, AsyncDelay, AsyncI2c};
+
+        let i2c = AsyncI2c { reads: vec![Ok(word_frame(0x0002))], ..Default::default() };
+        let mut sht = Sht3x::new(i2c, Address::Low).with_verify_commands(true);
+
+        let result = block_on(sht.start_periodic_async(Rate::R1, Repeatability::High, &mut AsyncDelay::default()));
+        assert!(matches!(result, Err(Error::CommandRejected)));
+        assert_eq!(sht.mode(), Mode::SingleShot);
+        assert_eq!(sht.i2c.writes, [[0x21, 0x30], [0xF3, 0x2D]]);
     }
 }